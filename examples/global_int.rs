@@ -8,8 +8,8 @@ memflex::global! {
 
 fn main() {
     unsafe {
-        assert_eq!(SOME_INT, *GLOBAL_INT);
+        assert_eq!(core::ptr::addr_of!(SOME_INT).read(), *GLOBAL_INT);
         SOME_INT += 10;
-        assert_eq!(SOME_INT, *GLOBAL_INT);
+        assert_eq!(core::ptr::addr_of!(SOME_INT).read(), *GLOBAL_INT);
     }
 }
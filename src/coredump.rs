@@ -0,0 +1,418 @@
+use crate::{types::Protection, MemorySource, MfError};
+use std::{fs, path::Path};
+
+pub(crate) const ET_CORE: u16 = 4;
+pub(crate) const PT_LOAD: u32 = 1;
+pub(crate) const PT_NOTE: u32 = 4;
+pub(crate) const NT_PRSTATUS: u32 = 1;
+pub(crate) const NT_FILE: u32 = 0x4649_4C45;
+/// `e_phnum` value telling that the real amount is in `sh_info` of the first section.
+pub(crate) const PN_XNUM: u16 = 0xFFFF;
+
+pub(crate) const EM_386: u16 = 3;
+pub(crate) const EM_ARM: u16 = 40;
+pub(crate) const EM_X86_64: u16 = 62;
+pub(crate) const EM_AARCH64: u16 = 183;
+
+/// Offset of `pr_pid` in `NT_PRSTATUS` for the pointer size.
+pub(crate) const fn prstatus_pid_offset(ptr_size: usize) -> usize {
+    16 + 2 * ptr_size
+}
+
+/// Offset of `pr_reg` in `NT_PRSTATUS` for the pointer size.
+pub(crate) const fn prstatus_regs_offset(ptr_size: usize) -> usize {
+    32 + 10 * ptr_size
+}
+
+/// Mapping of the dumped process, similar to a line of `/proc/<pid>/maps`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CoreRegion {
+    /// Start
+    pub from: usize,
+    /// End
+    pub to: usize,
+    /// Protection
+    pub prot: Protection,
+    /// Amount of bytes from the start stored in the dump, the rest can't be read.
+    pub stored: usize,
+    /// Mapped file, if any.
+    pub path: Option<String>,
+    /// Offset of the mapping in the file.
+    pub offset: u64,
+    file_offset: usize,
+}
+
+/// Thread of the dumped process with its registers.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CoreThread {
+    /// Id of the thread.
+    pub id: u32,
+    /// Signal the thread was stopped by, `0` if none.
+    pub signal: u16,
+    /// General purpose registers in the order of the platform's `user_regs_struct`.
+    /// Empty if the registers were not saved.
+    pub registers: Vec<u64>,
+    machine: u16,
+}
+
+impl CoreThread {
+    /// Returns the instruction pointer, `None` on unknown architectures.
+    pub fn instruction_pointer(&self) -> Option<u64> {
+        let i = match self.machine {
+            EM_X86_64 => 16,
+            EM_386 => 12,
+            EM_AARCH64 => 32,
+            EM_ARM => 15,
+            _ => return None,
+        };
+        self.registers.get(i).copied()
+    }
+
+    /// Returns the stack pointer, `None` on unknown architectures.
+    pub fn stack_pointer(&self) -> Option<u64> {
+        let i = match self.machine {
+            EM_X86_64 => 19,
+            EM_386 => 15,
+            EM_AARCH64 => 31,
+            EM_ARM => 13,
+            _ => return None,
+        };
+        self.registers.get(i).copied()
+    }
+}
+
+/// File mapped into the dumped process, from the `NT_FILE` note.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CoreFile {
+    /// Start
+    pub from: usize,
+    /// End
+    pub to: usize,
+    /// Offset of the mapping in the file.
+    pub offset: u64,
+    /// Path to the file.
+    pub path: String,
+}
+
+/// ELF core dump loaded in memory, read as the memory of the dumped process.
+/// ```no_run
+/// # use memflex::{CoreDump, MemorySource};
+/// let core = CoreDump::open("core.1234")?;
+/// let module = core.maps().iter().find(|r| r.path.as_deref() == Some("/opt/game/game")).unwrap();
+///
+/// let hit = core.find_pattern(memflex::ida_pat!("48 8B 05 ? ? ? ?"), module.from, module.stored).next();
+/// let health = core.resolve_multilevel(module.from + 0x1234, &[0x10, 0x8])?;
+/// println!("{hit:X?} {}", core.read::<f32>(health)?);
+/// # Ok::<_, memflex::MfError>(())
+/// ```
+#[derive(Debug, Clone)]
+pub struct CoreDump {
+    data: Vec<u8>,
+    ptr_size: usize,
+    machine: u16,
+    regions: Vec<CoreRegion>,
+    threads: Vec<CoreThread>,
+    files: Vec<CoreFile>,
+}
+
+/// Little or big endian reader of ELF fields, words are pointer sized.
+struct Fields<'a> {
+    data: &'a [u8],
+    little: bool,
+    wide: bool,
+}
+
+impl Fields<'_> {
+    fn bytes<const N: usize>(&self, offset: usize) -> crate::Result<[u8; N]> {
+        self.data
+            .get(offset..offset.checked_add(N).ok_or(MfError::InvalidElf)?)
+            .map(|b| b.try_into().unwrap())
+            .ok_or(MfError::InvalidElf)
+    }
+
+    fn u16(&self, offset: usize) -> crate::Result<u16> {
+        let b = self.bytes(offset)?;
+        Ok(if self.little {
+            u16::from_le_bytes(b)
+        } else {
+            u16::from_be_bytes(b)
+        })
+    }
+
+    fn u32(&self, offset: usize) -> crate::Result<u32> {
+        let b = self.bytes(offset)?;
+        Ok(if self.little {
+            u32::from_le_bytes(b)
+        } else {
+            u32::from_be_bytes(b)
+        })
+    }
+
+    fn u64(&self, offset: usize) -> crate::Result<u64> {
+        let b = self.bytes(offset)?;
+        Ok(if self.little {
+            u64::from_le_bytes(b)
+        } else {
+            u64::from_be_bytes(b)
+        })
+    }
+
+    fn word(&self, offset: usize) -> crate::Result<u64> {
+        if self.wide {
+            self.u64(offset)
+        } else {
+            self.u32(offset).map(u64::from)
+        }
+    }
+
+    fn usize(&self, offset: usize) -> crate::Result<usize> {
+        usize::try_from(self.word(offset)?).map_err(|_| MfError::InvalidElf)
+    }
+
+    fn with<'d>(&self, data: &'d [u8]) -> Fields<'d> {
+        Fields {
+            data,
+            little: self.little,
+            wide: self.wide,
+        }
+    }
+}
+
+impl CoreDump {
+    /// Reads the core dump at `path`.
+    pub fn open(path: impl AsRef<Path>) -> crate::Result<Self> {
+        Self::parse(fs::read(path)?)
+    }
+
+    /// Parses the contents of a core dump.
+    /// # Behavior
+    /// Segments cut off by the end of the file are shortened.
+    /// # Errors
+    /// [`MfError::InvalidElf`] if the data isn't an ELF core dump.
+    pub fn parse(data: Vec<u8>) -> crate::Result<Self> {
+        let (wide, little) = match data.get(..6) {
+            Some([0x7F, b'E', b'L', b'F', class @ (1 | 2), endian @ (1 | 2)]) => {
+                (*class == 2, *endian == 1)
+            }
+            _ => return Err(MfError::InvalidElf),
+        };
+        let f = Fields {
+            data: &data,
+            little,
+            wide,
+        };
+        let w = if wide { 8 } else { 4 };
+
+        if f.u16(16)? != ET_CORE {
+            return Err(MfError::InvalidElf);
+        }
+        let machine = f.u16(18)?;
+        let phoff = f.usize(24 + w)?;
+        let shoff = f.usize(24 + 2 * w)?;
+        let phentsize = f.u16(30 + 3 * w)? as usize;
+        let phnum = match f.u16(32 + 3 * w)? {
+            PN_XNUM => f.u32(shoff.checked_add(12 + 4 * w).ok_or(MfError::InvalidElf)?)? as usize,
+            n => n as usize,
+        };
+
+        let mut regions = vec![];
+        let mut notes = vec![];
+        for i in 0..phnum {
+            let ph = phentsize
+                .checked_mul(i)
+                .and_then(|o| o.checked_add(phoff))
+                .ok_or(MfError::InvalidElf)?;
+
+            let (kind, flags, offset, vaddr, filesz, memsz) = if wide {
+                (
+                    f.u32(ph)?,
+                    f.u32(ph + 4)?,
+                    f.usize(ph + 8)?,
+                    f.usize(ph + 16)?,
+                    f.usize(ph + 32)?,
+                    f.usize(ph + 40)?,
+                )
+            } else {
+                (
+                    f.u32(ph)?,
+                    f.u32(ph + 24)?,
+                    f.usize(ph + 4)?,
+                    f.usize(ph + 8)?,
+                    f.usize(ph + 16)?,
+                    f.usize(ph + 20)?,
+                )
+            };
+
+            let available = data.len().saturating_sub(offset).min(filesz);
+            match kind {
+                PT_LOAD => {
+                    let mut prot = Protection::empty();
+                    for (bit, p) in [(4, Protection::R), (2, Protection::W), (1, Protection::X)] {
+                        if flags & bit != 0 {
+                            prot |= p;
+                        }
+                    }
+
+                    regions.push(CoreRegion {
+                        from: vaddr,
+                        to: vaddr.checked_add(memsz).ok_or(MfError::InvalidElf)?,
+                        prot,
+                        stored: available.min(memsz),
+                        path: None,
+                        offset: 0,
+                        file_offset: offset,
+                    });
+                }
+                PT_NOTE => notes.push(&data[offset.min(data.len())..][..available]),
+                _ => {}
+            }
+        }
+
+        regions.sort_unstable_by_key(|r| r.from);
+        if regions.windows(2).any(|w| w[0].to > w[1].from) {
+            return Err(MfError::InvalidElf);
+        }
+
+        let mut threads = vec![];
+        let mut files = vec![];
+        for notes in notes {
+            let mut pos = 0;
+            while pos + 12 <= notes.len() {
+                let n = f.with(notes);
+                let namesz = n.u32(pos)? as usize;
+                let descsz = n.u32(pos + 4)? as usize;
+                let kind = n.u32(pos + 8)?;
+
+                let desc = pos + 12 + namesz.next_multiple_of(4);
+                let desc = notes
+                    .get(desc..desc.checked_add(descsz).ok_or(MfError::InvalidElf)?)
+                    .ok_or(MfError::InvalidElf)?;
+                pos += 12 + namesz.next_multiple_of(4) + descsz.next_multiple_of(4);
+
+                match kind {
+                    NT_PRSTATUS => threads.push(Self::parse_prstatus(&f.with(desc), machine)?),
+                    NT_FILE => files.extend(Self::parse_file(&f.with(desc))?),
+                    _ => {}
+                }
+            }
+        }
+
+        for region in &mut regions {
+            if let Some(file) = files.iter().find(|f| f.from == region.from) {
+                region.path = Some(file.path.clone());
+                region.offset = file.offset;
+            }
+        }
+
+        Ok(Self {
+            ptr_size: w,
+            machine,
+            regions,
+            threads,
+            files,
+            data,
+        })
+    }
+
+    fn parse_prstatus(f: &Fields, machine: u16) -> crate::Result<CoreThread> {
+        let w = if f.wide { 8 } else { 4 };
+        let regs = prstatus_regs_offset(w);
+        let count = f.data.len().saturating_sub(regs + w) / w;
+
+        Ok(CoreThread {
+            id: f.u32(prstatus_pid_offset(w))?,
+            signal: f.u16(12)?,
+            registers: (0..count)
+                .map(|i| f.word(regs + i * w))
+                .collect::<crate::Result<_>>()?,
+            machine,
+        })
+    }
+
+    fn parse_file(f: &Fields) -> crate::Result<Vec<CoreFile>> {
+        let w = if f.wide { 8 } else { 4 };
+        let count = f.usize(0)?;
+        let page_size = f.word(w)?;
+
+        let mut names = f
+            .data
+            .get(count.checked_mul(3 * w).ok_or(MfError::InvalidElf)? + 2 * w..)
+            .ok_or(MfError::InvalidElf)?
+            .split(|b| *b == 0);
+
+        (0..count)
+            .map(|i| {
+                let entry = 2 * w + i * 3 * w;
+                Ok(CoreFile {
+                    from: f.usize(entry)?,
+                    to: f.usize(entry + w)?,
+                    offset: f.word(entry + 2 * w)?.wrapping_mul(page_size),
+                    path: String::from_utf8_lossy(names.next().ok_or(MfError::InvalidElf)?)
+                        .into_owned(),
+                })
+            })
+            .collect()
+    }
+
+    /// ELF machine of the dumped process, e.g. `62` for x86-64.
+    #[inline]
+    pub fn machine(&self) -> u16 {
+        self.machine
+    }
+
+    /// Mappings of the dumped process sorted by address.
+    #[inline]
+    pub fn maps(&self) -> &[CoreRegion] {
+        &self.regions
+    }
+
+    /// Threads of the dumped process, the first one usually caused the dump.
+    #[inline]
+    pub fn threads(&self) -> &[CoreThread] {
+        &self.threads
+    }
+
+    /// Files mapped into the dumped process.
+    #[inline]
+    pub fn files(&self) -> &[CoreFile] {
+        &self.files
+    }
+
+    /// Returns the region that contains `address`.
+    fn region_at(&self, address: usize) -> Option<&CoreRegion> {
+        let i = self.regions.partition_point(|r| r.from <= address);
+        (i > 0 && address < self.regions[i - 1].to).then(|| &self.regions[i - 1])
+    }
+}
+
+impl MemorySource for CoreDump {
+    /// Reads stored bytes, stopping at the end of the region that contains `address`.
+    fn read_buf(&self, address: usize, buf: &mut [u8]) -> crate::Result<usize> {
+        let region = self
+            .region_at(address)
+            .filter(|r| address - r.from < r.stored)
+            .ok_or(MfError::InvalidAddress)?;
+        let from = address - region.from;
+        let len = buf.len().min(region.stored - from);
+
+        let start = region.file_offset + from;
+        buf[..len].copy_from_slice(&self.data[start..start + len]);
+        Ok(len)
+    }
+
+    #[inline]
+    fn pointer_size(&self) -> crate::Result<usize> {
+        Ok(self.ptr_size)
+    }
+
+    fn slice(&self, address: usize, len: usize) -> Option<&[u8]> {
+        let region = self.region_at(address)?;
+        let from = address - region.from;
+        if from.checked_add(len)? > region.stored {
+            return None;
+        }
+
+        let start = region.file_offset + from;
+        Some(&self.data[start..start + len])
+    }
+}
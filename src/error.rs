@@ -17,8 +17,26 @@ pub enum MfError {
     NoThreads,
     /// String read was not valid UTF-8 or UTF-16 byte sequence
     InvalidString,
+    /// String terminator was not found within the length limit
+    UnterminatedString,
     /// Process has died and is no longer available
     ProcessDied,
+    /// File is not a valid ELF file or has unsupported class
+    InvalidElf,
+    /// File is not a valid minidump
+    InvalidMinidump,
+    /// Memory at the address can't be accessed
+    InvalidAddress,
+    /// Instruction can't be decoded or relocated
+    InvalidInstruction,
+    /// No free memory close enough to the address
+    NoNearMemory,
+    /// Function called in the traced process faulted with the signal
+    #[cfg(unix)]
+    CallFaulted(i32),
+    /// I/O error
+    #[cfg(feature = "std")]
+    Io(std::io::Error),
 }
 
 #[allow(dead_code)]
@@ -45,5 +63,12 @@ impl Display for MfError {
 #[cfg(feature = "std")]
 impl std::error::Error for MfError {}
 
+#[cfg(feature = "std")]
+impl From<std::io::Error> for MfError {
+    fn from(e: std::io::Error) -> Self {
+        Self::Io(e)
+    }
+}
+
 #[allow(missing_docs)]
 pub type Result<T> = core::result::Result<T, MfError>;
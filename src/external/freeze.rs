@@ -0,0 +1,166 @@
+use super::{Location, OwnedProcess, Ticker};
+use crate::MfError;
+use core::{marker::PhantomData, mem::size_of, slice::from_raw_parts};
+use std::{
+    collections::HashMap,
+    sync::{
+        atomic::{AtomicU64, Ordering},
+        Arc, Mutex,
+    },
+    time::Duration,
+};
+
+struct Entry {
+    target: Location,
+    value: Vec<u8>,
+    paused: bool,
+}
+
+#[derive(Default)]
+struct Shared {
+    entries: Mutex<HashMap<u64, Entry>>,
+    next_id: AtomicU64,
+}
+
+/// Keeps writing values to the memory of a process on a background thread.
+/// ```no_run
+/// # use memflex::external::{find_process_by_name, Freezer, Location};
+/// # use std::time::Duration;
+/// let p = find_process_by_name("game")?;
+/// let freezer = Freezer::new(p, Duration::from_millis(10));
+///
+/// let health = freezer.freeze(
+///     Location::Multilevel { base: 0x5612_3456_0000, offsets: vec![0x1F_A0C8, 0x10] },
+///     100_f32,
+/// );
+/// health.set(200.);
+///
+/// // Stops freezing.
+/// drop(health);
+/// # Ok::<_, memflex::MfError>(())
+/// ```
+pub struct Freezer {
+    process: Arc<OwnedProcess>,
+    shared: Arc<Shared>,
+    ticker: Ticker,
+}
+
+impl Freezer {
+    /// Starts the background thread that writes frozen values every `interval`.
+    /// # Behavior
+    /// Failed writes are skipped until the next tick. The thread exits once the process dies.
+    pub fn new(process: impl Into<Arc<OwnedProcess>>, interval: Duration) -> Self {
+        let process = process.into();
+        let shared = Arc::new(Shared::default());
+
+        let ticker = Ticker::spawn(interval, {
+            let (process, shared) = (process.clone(), shared.clone());
+
+            move || {
+                for entry in shared.entries.lock().unwrap().values() {
+                    if entry.paused {
+                        continue;
+                    }
+
+                    let result = entry
+                        .target
+                        .resolve(&process)
+                        .and_then(|address| process.write_buf(address, &entry.value[..]));
+                    if let Err(MfError::ProcessDied) = result {
+                        return false;
+                    }
+                }
+
+                true
+            }
+        });
+
+        Self {
+            process,
+            shared,
+            ticker,
+        }
+    }
+
+    /// Process the values are written to.
+    #[inline]
+    pub fn process(&self) -> &Arc<OwnedProcess> {
+        &self.process
+    }
+
+    /// Checks if the background thread is still writing values.
+    pub fn is_running(&self) -> bool {
+        self.ticker.is_running()
+    }
+
+    /// Starts freezing the value at the target, until the returned handle is dropped.
+    pub fn freeze<T: Copy>(&self, target: impl Into<Location>, value: T) -> FrozenValue<T> {
+        let id = self.shared.next_id.fetch_add(1, Ordering::Relaxed);
+        self.shared.entries.lock().unwrap().insert(
+            id,
+            Entry {
+                target: target.into(),
+                value: bytes_of(&value).to_vec(),
+                paused: false,
+            },
+        );
+
+        FrozenValue {
+            shared: self.shared.clone(),
+            id,
+            _ph: PhantomData,
+        }
+    }
+}
+
+/// Handle to a value frozen by [`Freezer`]. Dropping it stops freezing.
+pub struct FrozenValue<T> {
+    shared: Arc<Shared>,
+    id: u64,
+    _ph: PhantomData<fn(T)>,
+}
+
+impl<T: Copy> FrozenValue<T> {
+    fn with_entry<R>(&self, f: impl FnOnce(&mut Entry) -> R) -> R {
+        let mut entries = self.shared.entries.lock().unwrap();
+        f(entries.get_mut(&self.id).expect("Frozen entry is missing"))
+    }
+
+    /// Changes the value written on every tick.
+    pub fn set(&self, value: T) {
+        self.with_entry(|e| e.value.copy_from_slice(bytes_of(&value)));
+    }
+
+    /// Changes the target the value is written to.
+    pub fn set_target(&self, target: impl Into<Location>) {
+        let target = target.into();
+        self.with_entry(|e| e.target = target);
+    }
+
+    /// Stops writing the value until [`FrozenValue::resume`] is called.
+    pub fn pause(&self) {
+        self.with_entry(|e| e.paused = true);
+    }
+
+    /// Resumes writing the value after [`FrozenValue::pause`].
+    pub fn resume(&self) {
+        self.with_entry(|e| e.paused = false);
+    }
+
+    /// Checks if the value is paused.
+    pub fn is_paused(&self) -> bool {
+        self.with_entry(|e| e.paused)
+    }
+}
+
+impl<T> Drop for FrozenValue<T> {
+    fn drop(&mut self) {
+        if let Ok(mut entries) = self.shared.entries.lock() {
+            entries.remove(&self.id);
+        }
+    }
+}
+
+fn bytes_of<T: Copy>(value: &T) -> &[u8] {
+    unsafe { from_raw_parts(value as *const T as *const u8, size_of::<T>()) }
+}
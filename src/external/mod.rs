@@ -8,9 +8,25 @@ mod unix;
 #[cfg(unix)]
 pub use unix::*;
 
-use crate::types::Protection;
+mod scan;
+pub use scan::*;
+mod freeze;
+pub use freeze::*;
+mod watch;
+pub use watch::*;
+mod ptr;
+pub use ptr::*;
+mod tree;
+pub use tree::*;
 
-#[derive(Debug)]
+use crate::{types::Protection, MemorySink, MemorySource};
+use std::{
+    sync::mpsc::{self, RecvTimeoutError, Sender},
+    thread::JoinHandle,
+    time::{Duration, Instant},
+};
+
+#[derive(Debug, Clone, PartialEq, Eq)]
 /// Single process
 pub struct ProcessEntry {
     /// Id of the process.
@@ -19,6 +35,13 @@ pub struct ProcessEntry {
     pub name: String,
     /// Id of the parent process.
     pub parent_id: u32,
+    /// Full path to the executable in the process's mount namespace,
+    /// `None` if it can't be read.
+    #[cfg(unix)]
+    pub path: Option<String>,
+    /// Ids of the process in nested PID namespaces, see `OwnedProcess::namespace_ids`.
+    #[cfg(unix)]
+    pub namespace_ids: Vec<u32>,
 }
 
 #[cfg(windows)]
@@ -52,11 +75,10 @@ impl ProcessEntry {
 
     /// Opens process by the entry's process id.
     /// # Behavior
-    /// Due to the way [`OwnedProcess`] works on unix, this function always
-    /// returns Ok(P).
+    /// Fails only if the process has already exited.
     #[cfg(unix)]
     pub fn open(&self) -> crate::Result<OwnedProcess> {
-        Ok(OwnedProcess(self.id))
+        OwnedProcess::open(self.id)
     }
 }
 
@@ -70,3 +92,186 @@ pub struct MemoryRegion {
     /// Prtection
     pub prot: Protection,
 }
+
+/// Timeout and polling interval used by `wait_for_*` functions.
+/// ```
+/// # use memflex::external::Wait;
+/// # use std::time::Duration;
+/// let wait = Wait::from(Duration::from_secs(10)).interval(Duration::from_millis(50));
+/// assert_eq!(wait.timeout, Some(Duration::from_secs(10)));
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Wait {
+    /// Maximum time to wait, `None` to wait forever.
+    pub timeout: Option<Duration>,
+    /// Maximum delay between two polls.
+    pub interval: Duration,
+}
+
+impl Wait {
+    /// Default maximum delay between two polls.
+    pub const DEFAULT_INTERVAL: Duration = Duration::from_millis(100);
+
+    /// Waits without a timeout.
+    pub const fn forever() -> Self {
+        Self {
+            timeout: None,
+            interval: Self::DEFAULT_INTERVAL,
+        }
+    }
+
+    /// Sets maximum delay between two polls.
+    pub const fn interval(mut self, interval: Duration) -> Self {
+        self.interval = interval;
+        self
+    }
+
+    /// Calls `f` until it returns `Some` or the timeout elapses.
+    /// # Behavior
+    /// First poll happens immediately, then the delay doubles starting from 1ms
+    /// until it reaches the interval. Returns `Ok(None)` on timeout.
+    pub(crate) fn poll<T>(
+        &self,
+        mut f: impl FnMut() -> crate::Result<Option<T>>,
+    ) -> crate::Result<Option<T>> {
+        let start = Instant::now();
+        let mut delay = Duration::from_millis(1).min(self.interval);
+
+        loop {
+            if let Some(v) = f()? {
+                return Ok(Some(v));
+            }
+
+            let sleep = match self.timeout {
+                Some(timeout) => match timeout.checked_sub(start.elapsed()) {
+                    Some(left) if !left.is_zero() => delay.min(left),
+                    _ => return Ok(None),
+                },
+                None => delay,
+            };
+
+            std::thread::sleep(sleep);
+            delay = (delay * 2).min(self.interval);
+        }
+    }
+}
+
+impl Default for Wait {
+    fn default() -> Self {
+        Self::forever()
+    }
+}
+
+impl From<Duration> for Wait {
+    fn from(timeout: Duration) -> Self {
+        Self::from(Some(timeout))
+    }
+}
+
+impl From<Option<Duration>> for Wait {
+    fn from(timeout: Option<Duration>) -> Self {
+        Self {
+            timeout,
+            interval: Self::DEFAULT_INTERVAL,
+        }
+    }
+}
+
+impl MemorySource for OwnedProcess {
+    #[inline]
+    fn read_buf(&self, address: usize, buf: &mut [u8]) -> crate::Result<usize> {
+        OwnedProcess::read_buf(self, address, buf)
+    }
+
+    #[inline]
+    fn pointer_size(&self) -> crate::Result<usize> {
+        OwnedProcess::pointer_size(self)
+    }
+}
+
+impl MemorySink for OwnedProcess {
+    #[inline]
+    fn write_buf(&mut self, address: usize, buf: &[u8]) -> crate::Result<usize> {
+        OwnedProcess::write_buf(self, address, buf)
+    }
+}
+
+impl MemorySink for &OwnedProcess {
+    #[inline]
+    fn write_buf(&mut self, address: usize, buf: &[u8]) -> crate::Result<usize> {
+        OwnedProcess::write_buf(self, address, buf)
+    }
+}
+
+/// Address in a process, either fixed or behind a pointer chain.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Location {
+    /// Fixed address.
+    Address(usize),
+    /// Pointer chain resolved with `OwnedProcess::resolve_multilevel` every time it's used.
+    Multilevel {
+        /// Base address of the chain.
+        base: usize,
+        /// Offsets of the chain.
+        offsets: Vec<usize>,
+    },
+}
+
+impl Location {
+    /// Resolves the location to an address in the process.
+    pub fn resolve(&self, process: &OwnedProcess) -> crate::Result<usize> {
+        match self {
+            Self::Address(address) => Ok(*address),
+            Self::Multilevel { base, offsets } => process.resolve_multilevel(*base, offsets),
+        }
+    }
+}
+
+impl From<usize> for Location {
+    fn from(address: usize) -> Self {
+        Self::Address(address)
+    }
+}
+
+/// Background thread that calls a function at a fixed interval until dropped.
+pub(crate) struct Ticker {
+    stop: Option<Sender<()>>,
+    thread: Option<JoinHandle<()>>,
+}
+
+impl Ticker {
+    /// Starts calling `tick` every `interval`, the thread exits once it returns `false`.
+    pub fn spawn(interval: Duration, mut tick: impl FnMut() -> bool + Send + 'static) -> Self {
+        let (stop, stopped) = mpsc::channel::<()>();
+
+        let thread = std::thread::spawn(move || {
+            while tick() {
+                if !matches!(
+                    stopped.recv_timeout(interval),
+                    Err(RecvTimeoutError::Timeout)
+                ) {
+                    break;
+                }
+            }
+        });
+
+        Self {
+            stop: Some(stop),
+            thread: Some(thread),
+        }
+    }
+
+    /// Checks if the thread is still running.
+    pub fn is_running(&self) -> bool {
+        self.thread.as_ref().is_some_and(|t| !t.is_finished())
+    }
+}
+
+impl Drop for Ticker {
+    fn drop(&mut self) {
+        drop(self.stop.take());
+        if let Some(t) = self.thread.take() {
+            _ = t.join();
+        }
+    }
+}
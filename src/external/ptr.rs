@@ -0,0 +1,585 @@
+use super::OwnedProcess;
+use crate::{Inherits, MemorySource};
+use core::{
+    any::type_name,
+    fmt,
+    hash::{Hash, Hasher},
+    marker::PhantomData,
+    mem::{size_of, MaybeUninit},
+    ptr::NonNull,
+    slice::{from_raw_parts, from_raw_parts_mut},
+};
+
+/// Structure that can be read from and written to the memory of another process.
+/// Implemented for numbers, pointers and arrays of them. Opt in for structs generated by
+/// [`makestruct`](crate::makestruct) with `unsafe impl RemoteStruct`, which also generates
+/// `<Name>Fields` trait with typed pointers to the fields.
+/// # Safety
+/// Any bytes must be a valid value of the type, so it must not contain references,
+/// `bool`s, enums and similar fields.
+/// ```no_run
+/// # use memflex::external::{find_process_by_name, RemoteStruct};
+/// memflex::makestruct! {
+///     #[derive(Clone, Copy)]
+///     pub struct Entity unsafe impl RemoteStruct {
+///         position: [f32; 3],
+///     }
+///
+///     #[derive(Clone, Copy)]
+///     pub struct Player unsafe impl RemoteStruct : pub Entity {
+///         health: f32,
+///         weapon: *const Weapon,
+///     }
+///
+///     #[derive(Clone, Copy)]
+///     pub struct Weapon unsafe impl RemoteStruct {
+///         ammo: u32,
+///     }
+/// }
+///
+/// let p = find_process_by_name("game")?;
+/// let player = Player::remote(&p, 0x5612_3456_7890);
+///
+/// let position = player.parent().read()?.position;
+/// let ammo = player.weapon().follow()?.read()?.ammo;
+///
+/// let mut copy = player.read()?;
+/// copy.health = 100.;
+/// copy.write_to(&p, player.address())?;
+/// # Ok::<_, memflex::MfError>(())
+/// ```
+/// Implementing it without `unsafe` is rejected.
+/// ```compile_fail
+/// # use memflex::external::RemoteStruct;
+/// memflex::makestruct! {
+///     pub struct Flags impl RemoteStruct {
+///         enabled: bool,
+///     }
+/// }
+/// ```
+/// Types that don't implement it can't be read.
+/// ```compile_fail
+/// # use memflex::external::{find_process_by_name, RemotePtr};
+/// let p = find_process_by_name("game")?;
+/// let enabled: bool = RemotePtr::<bool>::new(0x5612_3456_7890).read(&p)?;
+/// # Ok::<_, memflex::MfError>(())
+/// ```
+pub unsafe trait RemoteStruct: Sized {
+    /// Reads the structure at `address` in the process.
+    /// # Errors
+    /// [`MfError::InvalidAddress`](crate::MfError::InvalidAddress) if only part of
+    /// the structure could be read.
+    #[inline]
+    fn read_from(process: &OwnedProcess, address: usize) -> crate::Result<Self> {
+        RemotePtr::new(address).read(process)
+    }
+
+    /// Writes the structure at `address` in the process, returning amount of bytes written.
+    #[inline]
+    fn write_to(&self, process: &OwnedProcess, address: usize) -> crate::Result<usize> {
+        RemotePtr::new(address).write(process, self)
+    }
+
+    /// Returns the pointer to the structure at `address` in the process.
+    #[inline]
+    fn remote(process: &OwnedProcess, address: usize) -> BoundPtr<'_, Self> {
+        RemotePtr::new(address).bind(process)
+    }
+}
+
+macro_rules! impl_remote_struct {
+    ($($ty:ty),*) => {
+        $( unsafe impl RemoteStruct for $ty {} )*
+    };
+}
+
+impl_remote_struct!(u8, u16, u32, u64, u128, usize, i8, i16, i32, i64, i128, isize, f32, f64);
+
+unsafe impl<T: RemoteStruct, const N: usize> RemoteStruct for [T; N] {}
+unsafe impl<T> RemoteStruct for *const T {}
+unsafe impl<T> RemoteStruct for *mut T {}
+unsafe impl<T> RemoteStruct for Option<NonNull<T>> {}
+unsafe impl<T> RemoteStruct for RemotePtr<T> {}
+unsafe impl<T> RemoteStruct for RemotePtr32<T> {}
+
+/// Pointer type that can be followed in the memory of another process.
+/// # Behavior
+/// Pointers are read with the width of their type, so only [`RemotePtr32`] can be followed
+/// in 32-bit processes.
+pub trait RemotePointer: RemoteStruct + Copy {
+    /// Type of the value the pointer points to.
+    type Target;
+
+    /// Returns the address the pointer points to.
+    fn address(self) -> usize;
+}
+
+impl<T> RemotePointer for *const T {
+    type Target = T;
+
+    #[inline]
+    fn address(self) -> usize {
+        self as usize
+    }
+}
+
+impl<T> RemotePointer for *mut T {
+    type Target = T;
+
+    #[inline]
+    fn address(self) -> usize {
+        self as usize
+    }
+}
+
+impl<T> RemotePointer for Option<NonNull<T>> {
+    type Target = T;
+
+    #[inline]
+    fn address(self) -> usize {
+        self.map(|p| p.as_ptr() as usize).unwrap_or(0)
+    }
+}
+
+impl<T> RemotePointer for RemotePtr<T> {
+    type Target = T;
+
+    #[inline]
+    fn address(self) -> usize {
+        self.address
+    }
+}
+
+impl<T> RemotePointer for RemotePtr32<T> {
+    type Target = T;
+
+    #[inline]
+    fn address(self) -> usize {
+        self.address as usize
+    }
+}
+
+/// Typed address of `T` in the memory of another process.
+/// # Layout
+/// Has the same layout as `usize`, so it can be used inside of remote struct definitions.
+/// Because of that it only describes pointers of processes with the same pointer width,
+/// structs of 32-bit processes use [`RemotePtr32`].
+/// ```no_run
+/// # use memflex::external::{find_process_by_name, RemotePtr, RemoteStruct};
+/// #[repr(C)]
+/// #[derive(Clone, Copy)]
+/// struct Player {
+///     health: f32,
+///     weapon: RemotePtr<Weapon>,
+/// }
+/// unsafe impl RemoteStruct for Player {}
+///
+/// #[repr(C)]
+/// #[derive(Clone, Copy)]
+/// struct Weapon {
+///     ammo: u32,
+/// }
+/// unsafe impl RemoteStruct for Weapon {}
+///
+/// let p = find_process_by_name("game")?;
+/// let player = RemotePtr::<RemotePtr<Player>>::new(0x5612_3456_7890).bind(&p).deref()?;
+/// let ammo = player.read()?.weapon.bind(&p).read()?.ammo;
+///
+/// player.field::<f32>(0).write(&100.)?;
+/// # Ok::<_, memflex::MfError>(())
+/// ```
+#[repr(transparent)]
+pub struct RemotePtr<T> {
+    address: usize,
+    _ph: PhantomData<fn() -> T>,
+}
+
+impl<T> RemotePtr<T> {
+    /// Creates pointer from the address.
+    #[inline]
+    pub const fn new(address: usize) -> Self {
+        Self {
+            address,
+            _ph: PhantomData,
+        }
+    }
+
+    /// Creates null pointer.
+    #[inline]
+    pub const fn null() -> Self {
+        Self::new(0)
+    }
+
+    /// Returns the address.
+    #[inline]
+    pub const fn address(self) -> usize {
+        self.address
+    }
+
+    /// Checks if the pointer is null.
+    #[inline]
+    pub const fn is_null(self) -> bool {
+        self.address == 0
+    }
+
+    /// Moves the pointer by `count` elements of `T`.
+    #[inline]
+    pub const fn offset(self, count: isize) -> Self {
+        Self::new(
+            self.address
+                .wrapping_add_signed(count.wrapping_mul(size_of::<T>() as isize)),
+        )
+    }
+
+    /// Returns the pointer to a field of type `U` at `offset` bytes.
+    #[inline]
+    pub const fn field<U>(self, offset: usize) -> RemotePtr<U> {
+        RemotePtr::new(self.address.wrapping_add(offset))
+    }
+
+    /// Changes the type of the pointer.
+    #[inline]
+    pub const fn cast<U>(self) -> RemotePtr<U> {
+        RemotePtr::new(self.address)
+    }
+
+    /// Binds the pointer to the process, so it can be read and written.
+    #[inline]
+    pub const fn bind(self, process: &OwnedProcess) -> BoundPtr<'_, T> {
+        BoundPtr { ptr: self, process }
+    }
+
+    /// Reads the value from the process.
+    /// # Errors
+    /// [`MfError::InvalidAddress`](crate::MfError::InvalidAddress) if only part of
+    /// the value could be read.
+    pub fn read(self, process: &OwnedProcess) -> crate::Result<T>
+    where
+        T: RemoteStruct,
+    {
+        let mut value = MaybeUninit::<T>::uninit();
+        unsafe {
+            MemorySource::read_exact(
+                process,
+                self.address,
+                from_raw_parts_mut(value.as_mut_ptr().cast::<u8>(), size_of::<T>()),
+            )?;
+            // Any bytes are a valid value, see `RemoteStruct`.
+            Ok(value.assume_init())
+        }
+    }
+
+    /// Writes the value to the process, returning amount of bytes written.
+    pub fn write(self, process: &OwnedProcess, value: &T) -> crate::Result<usize> {
+        process.write_buf(self.address, unsafe {
+            from_raw_parts((value as *const T).cast::<u8>(), size_of::<T>())
+        })
+    }
+}
+
+impl<U> RemotePtr<RemotePtr<U>> {
+    /// Reads the pointer this one points to.
+    #[inline]
+    pub fn deref(self, process: &OwnedProcess) -> crate::Result<RemotePtr<U>> {
+        self.read(process)
+    }
+}
+
+impl<P: RemotePointer> RemotePtr<P> {
+    /// Reads the pointer this one points to, converting it to [`RemotePtr`].
+    #[inline]
+    pub fn follow(self, process: &OwnedProcess) -> crate::Result<RemotePtr<P::Target>> {
+        Ok(RemotePtr::new(self.read(process)?.address()))
+    }
+}
+
+impl<T> From<*const T> for RemotePtr<T> {
+    fn from(ptr: *const T) -> Self {
+        Self::new(ptr as usize)
+    }
+}
+
+impl<T> From<*mut T> for RemotePtr<T> {
+    fn from(ptr: *mut T) -> Self {
+        Self::new(ptr as usize)
+    }
+}
+
+impl<T> Clone for RemotePtr<T> {
+    fn clone(&self) -> Self {
+        *self
+    }
+}
+
+impl<T> Copy for RemotePtr<T> {}
+
+impl<T> PartialEq for RemotePtr<T> {
+    fn eq(&self, other: &Self) -> bool {
+        self.address == other.address
+    }
+}
+
+impl<T> Eq for RemotePtr<T> {}
+
+impl<T> Hash for RemotePtr<T> {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.address.hash(state)
+    }
+}
+
+impl<T> Default for RemotePtr<T> {
+    fn default() -> Self {
+        Self::null()
+    }
+}
+
+impl<T> From<usize> for RemotePtr<T> {
+    fn from(address: usize) -> Self {
+        Self::new(address)
+    }
+}
+
+impl<T> fmt::Debug for RemotePtr<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "RemotePtr<{}>({:#X})", type_name::<T>(), self.address)
+    }
+}
+
+/// Typed address of `T` in the memory of a 32-bit process.
+/// # Layout
+/// Has the same layout as `u32`, so it can be used inside of remote struct definitions
+/// of 32-bit processes. It's converted to [`RemotePtr`] to be read and written.
+/// ```no_run
+/// # use memflex::external::{find_process_by_name, RemotePtr, RemotePtr32, RemoteStruct};
+/// #[repr(C)]
+/// #[derive(Clone, Copy)]
+/// struct Player {
+///     health: f32,
+///     weapon: RemotePtr32<Weapon>,
+/// }
+/// unsafe impl RemoteStruct for Player {}
+///
+/// #[repr(C)]
+/// #[derive(Clone, Copy)]
+/// struct Weapon {
+///     ammo: u32,
+/// }
+/// unsafe impl RemoteStruct for Weapon {}
+///
+/// let p = find_process_by_name("game32")?;
+/// let player = RemotePtr::<Player>::new(0x0804_9000).bind(&p);
+/// let ammo = player.read()?.weapon.widen().bind(&p).read()?.ammo;
+/// # Ok::<_, memflex::MfError>(())
+/// ```
+#[repr(transparent)]
+pub struct RemotePtr32<T> {
+    address: u32,
+    _ph: PhantomData<fn() -> T>,
+}
+
+impl<T> RemotePtr32<T> {
+    /// Creates pointer from the address.
+    #[inline]
+    pub const fn new(address: u32) -> Self {
+        Self {
+            address,
+            _ph: PhantomData,
+        }
+    }
+
+    /// Creates null pointer.
+    #[inline]
+    pub const fn null() -> Self {
+        Self::new(0)
+    }
+
+    /// Returns the address.
+    #[inline]
+    pub const fn address(self) -> usize {
+        self.address as usize
+    }
+
+    /// Checks if the pointer is null.
+    #[inline]
+    pub const fn is_null(self) -> bool {
+        self.address == 0
+    }
+
+    /// Changes the type of the pointer.
+    #[inline]
+    pub const fn cast<U>(self) -> RemotePtr32<U> {
+        RemotePtr32::new(self.address)
+    }
+
+    /// Converts the pointer to [`RemotePtr`], so it can be read and written.
+    #[inline]
+    pub const fn widen(self) -> RemotePtr<T> {
+        RemotePtr::new(self.address as usize)
+    }
+}
+
+impl<T> From<RemotePtr32<T>> for RemotePtr<T> {
+    fn from(ptr: RemotePtr32<T>) -> Self {
+        ptr.widen()
+    }
+}
+
+impl<T> Clone for RemotePtr32<T> {
+    fn clone(&self) -> Self {
+        *self
+    }
+}
+
+impl<T> Copy for RemotePtr32<T> {}
+
+impl<T> PartialEq for RemotePtr32<T> {
+    fn eq(&self, other: &Self) -> bool {
+        self.address == other.address
+    }
+}
+
+impl<T> Eq for RemotePtr32<T> {}
+
+impl<T> Hash for RemotePtr32<T> {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.address.hash(state)
+    }
+}
+
+impl<T> Default for RemotePtr32<T> {
+    fn default() -> Self {
+        Self::null()
+    }
+}
+
+impl<T> From<u32> for RemotePtr32<T> {
+    fn from(address: u32) -> Self {
+        Self::new(address)
+    }
+}
+
+impl<T> fmt::Debug for RemotePtr32<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "RemotePtr32<{}>({:#X})", type_name::<T>(), self.address)
+    }
+}
+
+/// [`RemotePtr`] bound to the process it points into.
+pub struct BoundPtr<'p, T> {
+    ptr: RemotePtr<T>,
+    process: &'p OwnedProcess,
+}
+
+impl<'p, T> BoundPtr<'p, T> {
+    /// Returns the unbound pointer.
+    #[inline]
+    pub const fn ptr(&self) -> RemotePtr<T> {
+        self.ptr
+    }
+
+    /// Returns the process the pointer is bound to.
+    #[inline]
+    pub const fn process(&self) -> &'p OwnedProcess {
+        self.process
+    }
+
+    /// Returns the address.
+    #[inline]
+    pub const fn address(&self) -> usize {
+        self.ptr.address
+    }
+
+    /// Checks if the pointer is null.
+    #[inline]
+    pub const fn is_null(&self) -> bool {
+        self.ptr.is_null()
+    }
+
+    /// Moves the pointer by `count` elements of `T`.
+    #[inline]
+    pub const fn offset(&self, count: isize) -> Self {
+        self.ptr.offset(count).bind(self.process)
+    }
+
+    /// Returns the pointer to a field of type `U` at `offset` bytes.
+    #[inline]
+    pub const fn field<U>(&self, offset: usize) -> BoundPtr<'p, U> {
+        self.ptr.field(offset).bind(self.process)
+    }
+
+    /// Changes the type of the pointer.
+    #[inline]
+    pub const fn cast<U>(&self) -> BoundPtr<'p, U> {
+        self.ptr.cast().bind(self.process)
+    }
+
+    /// Reads the value from the process, see [`RemotePtr::read`].
+    #[inline]
+    pub fn read(&self) -> crate::Result<T>
+    where
+        T: RemoteStruct,
+    {
+        self.ptr.read(self.process)
+    }
+
+    /// Writes the value to the process, returning amount of bytes written.
+    #[inline]
+    pub fn write(&self, value: &T) -> crate::Result<usize> {
+        self.ptr.write(self.process, value)
+    }
+}
+
+impl<'p, U> BoundPtr<'p, RemotePtr<U>> {
+    /// Reads the pointer this one points to.
+    #[inline]
+    pub fn deref(&self) -> crate::Result<BoundPtr<'p, U>> {
+        Ok(self.ptr.deref(self.process)?.bind(self.process))
+    }
+}
+
+impl<'p, P: RemotePointer> BoundPtr<'p, P> {
+    /// Reads the pointer this one points to, converting it to [`BoundPtr`].
+    #[inline]
+    pub fn follow(&self) -> crate::Result<BoundPtr<'p, P::Target>> {
+        Ok(self.ptr.follow(self.process)?.bind(self.process))
+    }
+}
+
+impl<'p, T> BoundPtr<'p, T> {
+    /// Returns the pointer to the parent of a [`makestruct`](crate::makestruct) struct.
+    #[inline]
+    pub const fn parent<P>(&self) -> BoundPtr<'p, P>
+    where
+        T: Inherits<P>,
+    {
+        self.cast()
+    }
+}
+
+impl<T> Clone for BoundPtr<'_, T> {
+    fn clone(&self) -> Self {
+        *self
+    }
+}
+
+impl<T> Copy for BoundPtr<'_, T> {}
+
+impl<T> fmt::Debug for BoundPtr<'_, T> {
+    /// Shows the address as `module+offset` if it belongs to a module.
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "BoundPtr<{}>(", type_name::<T>())?;
+        if self.is_null() {
+            return write!(f, "null)");
+        }
+
+        let address = self.ptr.address;
+        let module = self.process.modules().ok().and_then(|mut m| {
+            m.find(|m| (m.base as usize..m.base as usize + m.size).contains(&address))
+        });
+
+        match module {
+            Some(m) => write!(f, "{}+{:#X})", m.name, address - m.base as usize),
+            None => write!(f, "{address:#X})"),
+        }
+    }
+}
@@ -0,0 +1,308 @@
+mod value;
+pub use value::*;
+mod store;
+pub use store::ScanStorage;
+mod pointer;
+pub use pointer::*;
+
+use super::{MemoryRegion, OwnedProcess};
+use crate::{Matcher, MfError};
+use core::{
+    marker::PhantomData,
+    mem::{align_of, size_of},
+};
+use store::{Chunk, Store};
+
+/// Amount of memory read at once.
+const BLOCK: usize = 0x10_0000;
+
+/// Searches the memory of a process for values of type `T`, narrowing down
+/// the results with each next scan.
+/// ```no_run
+/// # use memflex::external::{find_process_by_name, Scanner, ScanFilter, ScanStorage};
+/// let p = find_process_by_name("game")?;
+///
+/// let mut scanner = Scanner::<f32>::new(&p)
+///     .tolerance(0.01)
+///     .storage(ScanStorage::Disk("/tmp".into()));
+///
+/// scanner.first_unknown()?;
+/// // Health goes down ...
+/// scanner.next(ScanFilter::Decreased)?;
+/// // ... and then is restored to 100.
+/// scanner.next(ScanFilter::Exact(100.))?;
+///
+/// for (address, value) in scanner.results(10)? {
+///     println!("{address:#X}: {value}");
+/// }
+/// # Ok::<_, memflex::MfError>(())
+/// ```
+pub struct Scanner<'p, T> {
+    process: &'p OwnedProcess,
+    align: usize,
+    tolerance: T,
+    storage: ScanStorage,
+    region_filter: Box<dyn Fn(&MemoryRegion) -> bool + 'p>,
+    results: Option<Store>,
+    count: usize,
+    _ph: PhantomData<T>,
+}
+
+impl<'p, T: ScanValue> Scanner<'p, T> {
+    /// Creates new scanner over readable and writable regions of the process.
+    pub fn new(process: &'p OwnedProcess) -> Self {
+        Self {
+            process,
+            align: align_of::<T>(),
+            tolerance: T::ZERO,
+            storage: ScanStorage::Memory,
+            region_filter: Box::new(|r| r.prot.read() && r.prot.write()),
+            results: None,
+            count: 0,
+            _ph: PhantomData,
+        }
+    }
+
+    /// Sets alignment of scanned addresses, defaults to the alignment of `T`.
+    /// # Panics
+    /// If `align` is not a power of two or is greater than `4096`.
+    pub fn align(mut self, align: usize) -> Self {
+        assert!(align.is_power_of_two() && align <= 0x1000);
+        self.align = align;
+        self
+    }
+
+    /// Sets maximum difference at which values are considered equal.
+    pub fn tolerance(mut self, tolerance: T) -> Self {
+        self.tolerance = tolerance;
+        self
+    }
+
+    /// Sets where the results are stored.
+    pub fn storage(mut self, storage: ScanStorage) -> Self {
+        self.storage = storage;
+        self
+    }
+
+    /// Sets which regions are scanned by the first scan.
+    pub fn regions(mut self, filter: impl Fn(&MemoryRegion) -> bool + 'p) -> Self {
+        self.region_filter = Box::new(filter);
+        self
+    }
+
+    /// Amount of addresses found by the last scan.
+    #[inline]
+    pub fn len(&self) -> usize {
+        self.count
+    }
+
+    /// Checks if the last scan found nothing.
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.count == 0
+    }
+
+    /// Forgets all results, so the next scan has to be the first one.
+    pub fn reset(&mut self) {
+        self.results = None;
+        self.count = 0;
+    }
+
+    /// Reads every selected region block by block, keeping the chunks produced by `f`.
+    fn first_with(
+        &mut self,
+        mut f: impl FnMut(usize, Vec<u8>) -> Option<Chunk>,
+    ) -> crate::Result<usize> {
+        let width = size_of::<T>();
+        let mut store = Store::new(&self.storage)?;
+        let mut count = 0;
+
+        for region in self.process.maps()? {
+            if !(self.region_filter)(&region) {
+                continue;
+            }
+
+            for base in (region.from..region.to).step_by(BLOCK) {
+                let len = (BLOCK + width - 1).min(region.to - base);
+                if len < width {
+                    continue;
+                }
+
+                let mut buf = vec![0; len];
+                match self.process.read_buf(base, &mut buf[..]) {
+                    Ok(read) => buf.truncate(read),
+                    Err(MfError::ProcessDied) => return Err(MfError::ProcessDied),
+                    Err(_) => continue,
+                }
+
+                if let Some(chunk) = f(base, buf) {
+                    count += chunk.len(width, self.align);
+                    store.push(chunk)?;
+                }
+            }
+        }
+
+        store.seal()?;
+        self.results = Some(store);
+        self.count = count;
+
+        Ok(count)
+    }
+
+    /// Scans for the exact value, returning the amount of addresses found.
+    pub fn first_exact(&mut self, value: T) -> crate::Result<usize> {
+        let (width, align, tolerance) = (size_of::<T>(), self.align, self.tolerance);
+
+        self.first_with(|base, buf| {
+            let block = Chunk {
+                base,
+                offsets: None,
+                values: buf,
+            };
+
+            filter_chunk(&block, width, align, |_, bytes| {
+                T::from_bytes(bytes).approx_eq(value, tolerance)
+            })
+        })
+    }
+
+    /// Scans for the byte sequence matching the pattern, returning the amount of addresses found.
+    /// # Panics
+    /// If the length of the pattern is not equal to the size of `T`.
+    pub fn first_matching(&mut self, pat: impl Matcher) -> crate::Result<usize> {
+        let (width, align) = (size_of::<T>(), self.align);
+        assert_eq!(
+            pat.len(),
+            width,
+            "Pattern must have the same size as the value"
+        );
+
+        self.first_with(|base, buf| {
+            let block = Chunk {
+                base,
+                offsets: None,
+                values: buf,
+            };
+
+            filter_chunk(&block, width, align, |_, bytes| pat.matches(bytes))
+        })
+    }
+
+    /// Remembers every aligned address in the selected regions, returning the amount of them.
+    /// # Behavior
+    /// Memory is stored as is, so the results take as much space as the scanned regions.
+    pub fn first_unknown(&mut self) -> crate::Result<usize> {
+        self.first_with(|base, buf| {
+            Some(Chunk {
+                base,
+                offsets: None,
+                values: buf,
+            })
+        })
+    }
+
+    /// Narrows down the results of the previous scan, returning the amount of addresses left.
+    /// # Errors
+    /// [`MfError::ProcessDied`] if the process exited, the previous results are kept.
+    /// # Panics
+    /// If there was no first scan.
+    pub fn next(&mut self, filter: ScanFilter<T>) -> crate::Result<usize> {
+        let (width, align, tolerance) = (size_of::<T>(), self.align, self.tolerance);
+        let previous = self.results.as_ref().expect("First scan wasn't performed");
+
+        let mut store = Store::new(&self.storage)?;
+        let mut count = 0;
+
+        previous.for_each(|chunk| {
+            let mut current = vec![0; chunk.span(width)];
+            let read = match self.process.read_buf(chunk.base, &mut current[..]) {
+                Ok(read) => read,
+                Err(MfError::ProcessDied) => return Err(MfError::ProcessDied),
+                // Unmapped since the previous scan.
+                Err(_) => 0,
+            };
+
+            let next = filter_chunk(chunk, width, align, |offset, old| {
+                offset + width <= read
+                    && filter.test(
+                        T::from_bytes(old),
+                        T::from_bytes(&current[offset..offset + width]),
+                        tolerance,
+                    )
+            });
+
+            if let Some(mut next) = next {
+                // Remember the new values for the next scan.
+                for (o, v) in next
+                    .offsets
+                    .iter()
+                    .flatten()
+                    .zip(next.values.chunks_exact_mut(width))
+                {
+                    v.copy_from_slice(&current[*o as usize..*o as usize + width]);
+                }
+
+                count += next.len(width, align);
+                store.push(next)?;
+            }
+
+            Ok(true)
+        })?;
+
+        store.seal()?;
+        self.results = Some(store);
+        self.count = count;
+
+        Ok(count)
+    }
+
+    /// Returns up to `limit` addresses found by the last scan together with the values they
+    /// had at the time of the scan.
+    pub fn results(&self, limit: usize) -> crate::Result<Vec<(usize, T)>> {
+        let width = size_of::<T>();
+        let mut out = vec![];
+
+        if let Some(store) = &self.results {
+            store.for_each(|chunk| {
+                out.extend(
+                    chunk
+                        .entries(width, self.align)
+                        .take(limit - out.len())
+                        .map(|(o, v)| (chunk.base + o, T::from_bytes(v))),
+                );
+
+                Ok(out.len() < limit)
+            })?;
+        }
+
+        Ok(out)
+    }
+}
+
+/// Keeps only the entries of the chunk that pass the test, `None` if there are no such.
+fn filter_chunk(
+    chunk: &Chunk,
+    width: usize,
+    align: usize,
+    mut test: impl FnMut(usize, &[u8]) -> bool,
+) -> Option<Chunk> {
+    let mut offsets = vec![];
+    let mut values = vec![];
+
+    for (o, v) in chunk.entries(width, align) {
+        if test(o, v) {
+            offsets.push(o as u32);
+            values.extend_from_slice(v);
+        }
+    }
+
+    if offsets.is_empty() {
+        None
+    } else {
+        Some(Chunk {
+            base: chunk.base,
+            offsets: Some(offsets),
+            values,
+        })
+    }
+}
@@ -0,0 +1,274 @@
+use super::BLOCK;
+use crate::{external::OwnedProcess, types::ModuleInfoWithName, MfError};
+use std::collections::HashSet;
+
+/// Path from a static address in a module to the target.
+/// ```no_run
+/// # use memflex::external::{find_process_by_name, PointerChain};
+/// # let p = find_process_by_name("game")?;
+/// let chain = PointerChain {
+///     module: "libgame.so".into(),
+///     base_offset: 0x1F_A0C8,
+///     offsets: vec![0x10, 0x48],
+/// };
+///
+/// let module = p.find_module(&chain.module)?;
+/// assert_eq!(
+///     chain.resolve(&p)?,
+///     p.resolve_multilevel(module.base as usize, &chain.to_offsets())?
+/// );
+/// # Ok::<_, memflex::MfError>(())
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct PointerChain {
+    /// Name of the module that contains the first pointer.
+    pub module: String,
+    /// Offset of the first pointer from the module's base.
+    pub base_offset: usize,
+    /// Offsets added to each dereferenced pointer.
+    pub offsets: Vec<usize>,
+}
+
+impl PointerChain {
+    /// Returns offsets that together with the module's base can be passed to
+    /// [`OwnedProcess::resolve_multilevel`].
+    pub fn to_offsets(&self) -> Vec<usize> {
+        let mut out = Vec::with_capacity(self.offsets.len() + 1);
+        out.push(self.base_offset);
+        out.extend_from_slice(&self.offsets);
+        out
+    }
+
+    /// Resolves the chain in the process.
+    pub fn resolve(&self, process: &OwnedProcess) -> crate::Result<usize> {
+        let module = process.find_module(&self.module)?;
+        process.resolve_multilevel(module.base as usize, &self.to_offsets())
+    }
+
+    /// Keeps only the chains that resolve to `target` in the process.
+    /// # Usage
+    /// Pass the process and the target address from another run of the program
+    /// to get rid of chains that were valid by coincidence.
+    /// # Errors
+    /// [`MfError::ProcessDied`] if the process exited, the chains are kept.
+    pub fn retain_valid(
+        chains: &mut Vec<Self>,
+        process: &OwnedProcess,
+        target: usize,
+    ) -> crate::Result<()> {
+        let modules = process.modules()?.collect::<Vec<_>>();
+
+        let mut valid = Vec::with_capacity(chains.len());
+        for c in chains.iter() {
+            let Some(m) = modules
+                .iter()
+                .find(|m| m.name.eq_ignore_ascii_case(&c.module))
+            else {
+                valid.push(false);
+                continue;
+            };
+
+            match process.resolve_multilevel(m.base as usize, &c.to_offsets()) {
+                Ok(address) => valid.push(address == target),
+                Err(MfError::ProcessDied) => return Err(MfError::ProcessDied),
+                Err(_) => valid.push(false),
+            }
+        }
+
+        let mut valid = valid.into_iter();
+        chains.retain(|_| valid.next().unwrap());
+
+        Ok(())
+    }
+}
+
+/// Searches for pointer chains that lead from static memory of modules to the target address.
+/// ```no_run
+/// # use memflex::external::{find_process_by_name, PointerChain, PointerScanner};
+/// let p = find_process_by_name("game")?;
+/// let mut chains = PointerScanner::new(&p)
+///     .max_depth(4)
+///     .max_offset(0x800)
+///     .scan(0x5612_3456_7890)?;
+///
+/// // Restart the game and find the value again.
+/// let p = find_process_by_name("game")?;
+/// PointerChain::retain_valid(&mut chains, &p, 0x55AB_CDEF_0120)?;
+/// # Ok::<_, memflex::MfError>(())
+/// ```
+pub struct PointerScanner<'p> {
+    process: &'p OwnedProcess,
+    max_depth: usize,
+    max_offset: usize,
+    max_results: usize,
+    align: Option<usize>,
+}
+
+/// Address on the way from a static pointer to the target.
+struct Node {
+    /// Address that needs to be reached.
+    address: usize,
+    /// Offset from the pointer at `address` to the next node.
+    offset: usize,
+    /// Index of the next node, `None` for the target.
+    next: Option<usize>,
+}
+
+impl<'p> PointerScanner<'p> {
+    /// Creates new pointer scanner with depth of `5` and offset of `0x1000`.
+    pub fn new(process: &'p OwnedProcess) -> Self {
+        Self {
+            process,
+            max_depth: 5,
+            max_offset: 0x1000,
+            max_results: 10_000,
+            align: None,
+        }
+    }
+
+    /// Sets maximum amount of pointers in a chain.
+    pub fn max_depth(mut self, depth: usize) -> Self {
+        self.max_depth = depth;
+        self
+    }
+
+    /// Sets maximum offset added to a pointer.
+    pub fn max_offset(mut self, offset: usize) -> Self {
+        self.max_offset = offset;
+        self
+    }
+
+    /// Sets maximum amount of chains, after which the scan stops.
+    pub fn max_results(mut self, results: usize) -> Self {
+        self.max_results = results;
+        self
+    }
+
+    /// Sets alignment of pointers, defaults to the size of a pointer in the process.
+    pub fn align(mut self, align: usize) -> Self {
+        self.align = Some(align.max(1));
+        self
+    }
+
+    /// Collects every aligned pointer in writable memory that points into readable memory.
+    /// Returns `(value, address)` pairs sorted by value.
+    fn pointer_map(&self) -> crate::Result<Vec<(usize, usize)>> {
+        let ptr = self.process.pointer_size()?;
+        let align = self.align.unwrap_or(ptr);
+
+        let mut readable = self
+            .process
+            .maps()?
+            .into_iter()
+            .filter(|r| r.prot.read())
+            .map(|r| (r.from, r.to, r.prot.write()))
+            .collect::<Vec<_>>();
+        readable.sort_unstable();
+
+        let points_to_readable = |value: usize| {
+            let i = readable.partition_point(|r| r.0 <= value);
+            i > 0 && value < readable[i - 1].1
+        };
+
+        let mut pointers = vec![];
+        let mut buf = vec![0; BLOCK];
+
+        for &(from, to, writable) in &readable {
+            if !writable {
+                continue;
+            }
+
+            for base in (from..to).step_by(BLOCK) {
+                let len = BLOCK.min(to - base);
+                let read = match self.process.read_buf(base, &mut buf[..len]) {
+                    Ok(read) => read,
+                    Err(MfError::ProcessDied) => return Err(MfError::ProcessDied),
+                    Err(_) => continue,
+                };
+
+                for o in (0..read.saturating_sub(ptr - 1)).step_by(align) {
+                    let value = match ptr {
+                        4 => u32::from_ne_bytes(buf[o..o + 4].try_into().unwrap()) as usize,
+                        _ => usize::from_ne_bytes(buf[o..o + ptr].try_into().unwrap()),
+                    };
+                    if points_to_readable(value) {
+                        pointers.push((value, base + o));
+                    }
+                }
+            }
+        }
+
+        pointers.sort_unstable();
+        Ok(pointers)
+    }
+
+    /// Searches for chains leading to the target.
+    /// # Behavior
+    /// Chains end at the first static pointer found, and every address is visited only
+    /// once, so the chains through it with the shortest length are preferred.
+    pub fn scan(&self, target: usize) -> crate::Result<Vec<PointerChain>> {
+        let mut modules = self.process.modules()?.collect::<Vec<ModuleInfoWithName>>();
+        modules.sort_unstable_by_key(|m| m.base);
+        let module_of = |address: usize| {
+            let i = modules.partition_point(|m| m.base as usize <= address);
+            (i > 0 && address < modules[i - 1].base as usize + modules[i - 1].size)
+                .then(|| &modules[i - 1])
+        };
+
+        let pointers = self.pointer_map()?;
+
+        let mut nodes = vec![Node {
+            address: target,
+            offset: 0,
+            next: None,
+        }];
+        let mut visited = HashSet::from([target]);
+        let mut level = 0..1;
+        let mut chains = vec![];
+
+        for _ in 0..self.max_depth {
+            let start = nodes.len();
+
+            for n in level.clone() {
+                let address = nodes[n].address;
+                let from =
+                    pointers.partition_point(|p| p.0 < address.saturating_sub(self.max_offset));
+                let to = pointers.partition_point(|p| p.0 <= address);
+
+                for &(value, pointer) in &pointers[from..to] {
+                    if let Some(module) = module_of(pointer) {
+                        let mut offsets = vec![address - value];
+                        let mut i = n;
+                        while let Some(next) = nodes[i].next {
+                            offsets.push(nodes[i].offset);
+                            i = next;
+                        }
+
+                        chains.push(PointerChain {
+                            module: module.name.clone(),
+                            base_offset: pointer - module.base as usize,
+                            offsets,
+                        });
+
+                        if chains.len() >= self.max_results {
+                            return Ok(chains);
+                        }
+                    } else if visited.insert(pointer) {
+                        nodes.push(Node {
+                            address: pointer,
+                            offset: address - value,
+                            next: Some(n),
+                        });
+                    }
+                }
+            }
+
+            level = start..nodes.len();
+            if level.is_empty() {
+                break;
+            }
+        }
+
+        Ok(chains)
+    }
+}
@@ -0,0 +1,200 @@
+use std::{
+    fs::{self, File},
+    io::{BufReader, BufWriter, Read, Write},
+    path::PathBuf,
+    sync::atomic::{AtomicUsize, Ordering},
+};
+
+/// Where scan results are kept.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub enum ScanStorage {
+    /// In the memory of the current process.
+    #[default]
+    Memory,
+    /// In temporary files inside of the directory.
+    Disk(PathBuf),
+}
+
+/// Scan results within a single block of memory.
+pub(crate) struct Chunk {
+    /// Address of the block.
+    pub base: usize,
+    /// Offsets of entries from `base`. `None` if `values` is a copy of the block and
+    /// every aligned offset is an entry.
+    pub offsets: Option<Vec<u32>>,
+    /// Values of the entries one after another, or the block copy.
+    pub values: Vec<u8>,
+}
+
+impl Chunk {
+    /// Iterates over entries, yielding their offsets and value bytes.
+    pub fn entries(
+        &self,
+        width: usize,
+        align: usize,
+    ) -> Box<dyn Iterator<Item = (usize, &[u8])> + '_> {
+        match &self.offsets {
+            Some(offsets) => Box::new(
+                offsets
+                    .iter()
+                    .zip(self.values.chunks_exact(width))
+                    .map(|(o, v)| (*o as usize, v)),
+            ),
+            None => Box::new(
+                (0..self.values.len().saturating_sub(width - 1))
+                    .step_by(align)
+                    .map(move |o| (o, &self.values[o..o + width])),
+            ),
+        }
+    }
+
+    /// Amount of entries in the chunk.
+    pub fn len(&self, width: usize, align: usize) -> usize {
+        match &self.offsets {
+            Some(offsets) => offsets.len(),
+            None => self.values.len().saturating_sub(width - 1).div_ceil(align),
+        }
+    }
+
+    /// Amount of bytes the chunk spans from `base`.
+    pub fn span(&self, width: usize) -> usize {
+        match &self.offsets {
+            Some(offsets) => offsets.last().map(|o| *o as usize + width).unwrap_or(0),
+            None => self.values.len(),
+        }
+    }
+
+    fn write(&self, w: &mut impl Write) -> std::io::Result<()> {
+        w.write_all(&(self.base as u64).to_le_bytes())?;
+        match &self.offsets {
+            Some(offsets) => {
+                w.write_all(&[1])?;
+                w.write_all(&(offsets.len() as u32).to_le_bytes())?;
+                for o in offsets {
+                    w.write_all(&o.to_le_bytes())?;
+                }
+            }
+            None => w.write_all(&[0])?,
+        }
+        w.write_all(&(self.values.len() as u32).to_le_bytes())?;
+        w.write_all(&self.values)
+    }
+
+    fn read(r: &mut impl Read) -> std::io::Result<Self> {
+        fn array<const N: usize>(r: &mut impl Read) -> std::io::Result<[u8; N]> {
+            let mut buf = [0; N];
+            r.read_exact(&mut buf)?;
+            Ok(buf)
+        }
+
+        let base = u64::from_le_bytes(array(r)?) as usize;
+        let offsets = match array::<1>(r)? {
+            [0] => None,
+            _ => {
+                let len = u32::from_le_bytes(array(r)?) as usize;
+                let mut offsets = Vec::with_capacity(len);
+                for _ in 0..len {
+                    offsets.push(u32::from_le_bytes(array(r)?));
+                }
+                Some(offsets)
+            }
+        };
+
+        let mut values = vec![0; u32::from_le_bytes(array(r)?) as usize];
+        r.read_exact(&mut values)?;
+
+        Ok(Self {
+            base,
+            offsets,
+            values,
+        })
+    }
+}
+
+/// Sequence of chunks written once and read back in order.
+pub(crate) enum Store {
+    Memory(Vec<Chunk>),
+    Disk {
+        path: PathBuf,
+        writer: Option<BufWriter<File>>,
+        chunks: usize,
+    },
+}
+
+impl Store {
+    pub fn new(storage: &ScanStorage) -> crate::Result<Self> {
+        static COUNTER: AtomicUsize = AtomicUsize::new(0);
+
+        Ok(match storage {
+            ScanStorage::Memory => Self::Memory(vec![]),
+            ScanStorage::Disk(dir) => {
+                let path = dir.join(format!(
+                    "memflex-scan-{}-{}.bin",
+                    std::process::id(),
+                    COUNTER.fetch_add(1, Ordering::Relaxed)
+                ));
+
+                Self::Disk {
+                    writer: Some(BufWriter::new(File::create(&path)?)),
+                    path,
+                    chunks: 0,
+                }
+            }
+        })
+    }
+
+    pub fn push(&mut self, chunk: Chunk) -> crate::Result<()> {
+        match self {
+            Self::Memory(chunks) => chunks.push(chunk),
+            Self::Disk { writer, chunks, .. } => {
+                chunk.write(writer.as_mut().expect("Store is sealed"))?;
+                *chunks += 1;
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Finishes writing, making the store readable.
+    pub fn seal(&mut self) -> crate::Result<()> {
+        if let Self::Disk { writer, .. } = self {
+            if let Some(mut w) = writer.take() {
+                w.flush()?;
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Calls `f` on every chunk in the order they were pushed.
+    pub fn for_each(&self, mut f: impl FnMut(&Chunk) -> crate::Result<bool>) -> crate::Result<()> {
+        match self {
+            Self::Memory(chunks) => {
+                for c in chunks {
+                    if !f(c)? {
+                        break;
+                    }
+                }
+            }
+            Self::Disk { path, chunks, .. } => {
+                let mut r = BufReader::new(File::open(path)?);
+                for _ in 0..*chunks {
+                    if !f(&Chunk::read(&mut r)?)? {
+                        break;
+                    }
+                }
+            }
+        }
+
+        Ok(())
+    }
+}
+
+impl Drop for Store {
+    fn drop(&mut self) {
+        if let Self::Disk { path, writer, .. } = self {
+            drop(writer.take());
+            _ = fs::remove_file(path);
+        }
+    }
+}
@@ -0,0 +1,137 @@
+/// Value that can be searched for with [`super::Scanner`].
+pub trait ScanValue: Copy + PartialOrd + 'static {
+    /// Tolerance that makes [`ScanValue::approx_eq`] exact.
+    const ZERO: Self;
+
+    /// Reads the value from `size_of::<Self>()` bytes.
+    fn from_bytes(bytes: &[u8]) -> Self;
+
+    /// Checks if two values are equal, allowing them to differ by `tolerance`.
+    fn approx_eq(self, other: Self, tolerance: Self) -> bool;
+
+    /// Adds two values, `None` if the type doesn't support addition.
+    fn add(self, other: Self) -> Option<Self>;
+
+    /// Subtracts two values, `None` if the type doesn't support subtraction.
+    fn sub(self, other: Self) -> Option<Self>;
+}
+
+macro_rules! impl_int {
+    ($($int:ty),*) => {
+        $(
+            impl ScanValue for $int {
+                const ZERO: Self = 0;
+
+                #[inline]
+                fn from_bytes(bytes: &[u8]) -> Self {
+                    Self::from_ne_bytes(bytes.try_into().unwrap())
+                }
+
+                #[inline]
+                fn approx_eq(self, other: Self, tolerance: Self) -> bool {
+                    self.abs_diff(other) <= tolerance.abs_diff(0)
+                }
+
+                #[inline]
+                fn add(self, other: Self) -> Option<Self> {
+                    Some(self.wrapping_add(other))
+                }
+
+                #[inline]
+                fn sub(self, other: Self) -> Option<Self> {
+                    Some(self.wrapping_sub(other))
+                }
+            }
+        )*
+    };
+}
+impl_int!(u8, u16, u32, u64, usize, i8, i16, i32, i64, isize);
+
+macro_rules! impl_float {
+    ($($float:ty),*) => {
+        $(
+            impl ScanValue for $float {
+                const ZERO: Self = 0.;
+
+                #[inline]
+                fn from_bytes(bytes: &[u8]) -> Self {
+                    Self::from_ne_bytes(bytes.try_into().unwrap())
+                }
+
+                #[inline]
+                fn approx_eq(self, other: Self, tolerance: Self) -> bool {
+                    (self - other).abs() <= tolerance
+                }
+
+                #[inline]
+                fn add(self, other: Self) -> Option<Self> {
+                    Some(self + other)
+                }
+
+                #[inline]
+                fn sub(self, other: Self) -> Option<Self> {
+                    Some(self - other)
+                }
+            }
+        )*
+    };
+}
+impl_float!(f32, f64);
+
+impl<const N: usize> ScanValue for [u8; N] {
+    const ZERO: Self = [0; N];
+
+    #[inline]
+    fn from_bytes(bytes: &[u8]) -> Self {
+        bytes.try_into().unwrap()
+    }
+
+    #[inline]
+    fn approx_eq(self, other: Self, _: Self) -> bool {
+        self == other
+    }
+
+    #[inline]
+    fn add(self, _: Self) -> Option<Self> {
+        None
+    }
+
+    #[inline]
+    fn sub(self, _: Self) -> Option<Self> {
+        None
+    }
+}
+
+/// Filter used to narrow down results of the previous scan.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ScanFilter<T> {
+    /// Value is equal to the specified one.
+    Exact(T),
+    /// Value has changed since the previous scan.
+    Changed,
+    /// Value hasn't changed since the previous scan.
+    Unchanged,
+    /// Value is greater than during the previous scan.
+    Increased,
+    /// Value is less than during the previous scan.
+    Decreased,
+    /// Value has increased exactly by the specified amount.
+    IncreasedBy(T),
+    /// Value has decreased exactly by the specified amount.
+    DecreasedBy(T),
+}
+
+impl<T: ScanValue> ScanFilter<T> {
+    /// Checks if the value passes the filter.
+    pub fn test(&self, old: T, new: T, tolerance: T) -> bool {
+        match *self {
+            Self::Exact(v) => new.approx_eq(v, tolerance),
+            Self::Changed => !new.approx_eq(old, tolerance),
+            Self::Unchanged => new.approx_eq(old, tolerance),
+            Self::Increased => new > old && !new.approx_eq(old, tolerance),
+            Self::Decreased => new < old && !new.approx_eq(old, tolerance),
+            Self::IncreasedBy(d) => old.add(d).is_some_and(|v| new.approx_eq(v, tolerance)),
+            Self::DecreasedBy(d) => old.sub(d).is_some_and(|v| new.approx_eq(v, tolerance)),
+        }
+    }
+}
@@ -0,0 +1,147 @@
+use super::{OwnedProcess, ProcessEntry, ProcessIterator};
+use crate::MfError;
+use std::collections::{HashMap, HashSet};
+
+/// Snapshot of the parent-child hierarchy of processes in the system.
+/// # Behavior
+/// Ids of exited parents can be reused by unrelated processes, which then look like
+/// parents of processes started before them.
+/// ```no_run
+/// # use memflex::external::ProcessTree;
+/// let tree = ProcessTree::new()?;
+/// let launcher = tree.iter().find(|p| p.name == "launcher").unwrap();
+/// let game = tree
+///     .descendants(launcher.id)
+///     .find(|p| p.name == "game")
+///     .unwrap();
+/// println!("{:?}", tree.ancestors(game.id).collect::<Vec<_>>());
+/// # Ok::<_, memflex::MfError>(())
+/// ```
+#[derive(Debug, Clone)]
+pub struct ProcessTree {
+    entries: Vec<ProcessEntry>,
+    index: HashMap<u32, usize>,
+    children: HashMap<u32, Vec<usize>>,
+}
+
+impl ProcessTree {
+    /// Takes a snapshot of processes in the system.
+    /// # Errors
+    /// If any of the processes can't be read, see [`ProcessIterator`].
+    pub fn new() -> crate::Result<Self> {
+        let entries = ProcessIterator::new()?.collect::<crate::Result<Vec<_>>>()?;
+
+        Ok(Self::from_entries(entries))
+    }
+
+    /// Builds the tree from the process entries.
+    pub fn from_entries(entries: impl IntoIterator<Item = ProcessEntry>) -> Self {
+        let entries = entries.into_iter().collect::<Vec<_>>();
+        let mut index = HashMap::new();
+        let mut children = HashMap::<_, Vec<_>>::new();
+
+        for (i, e) in entries.iter().enumerate() {
+            index.insert(e.id, i);
+            // Some system processes are their own parents.
+            if e.parent_id != e.id {
+                children.entry(e.parent_id).or_default().push(i);
+            }
+        }
+
+        Self {
+            entries,
+            index,
+            children,
+        }
+    }
+
+    /// Returns an iterator over all processes in the tree.
+    pub fn iter(&self) -> impl Iterator<Item = &ProcessEntry> {
+        self.entries.iter()
+    }
+
+    /// Returns the process with the id.
+    pub fn get(&self, id: u32) -> Option<&ProcessEntry> {
+        self.index.get(&id).map(|i| &self.entries[*i])
+    }
+
+    /// Returns the parent of the process, `None` if either of them isn't in the tree.
+    pub fn parent(&self, id: u32) -> Option<&ProcessEntry> {
+        self.get(id)
+            .filter(|e| e.parent_id != e.id)
+            .and_then(|e| self.get(e.parent_id))
+    }
+
+    /// Returns an iterator over direct children of the process.
+    pub fn children(&self, id: u32) -> impl Iterator<Item = &ProcessEntry> {
+        self.children
+            .get(&id)
+            .into_iter()
+            .flatten()
+            .map(|i| &self.entries[*i])
+    }
+
+    /// Returns an iterator over children of the process, their children and so on.
+    /// # Behavior
+    /// Every child comes before its own children.
+    pub fn descendants(&self, id: u32) -> impl Iterator<Item = &ProcessEntry> {
+        let mut stack = self.children(id).collect::<Vec<_>>();
+        stack.reverse();
+        let mut seen = HashSet::from([id]);
+
+        core::iter::from_fn(move || loop {
+            let next = stack.pop()?;
+            if seen.insert(next.id) {
+                let len = stack.len();
+                stack.extend(self.children(next.id));
+                stack[len..].reverse();
+                return Some(next);
+            }
+        })
+    }
+
+    /// Returns an iterator over the parent of the process, its parent and so on.
+    pub fn ancestors(&self, id: u32) -> impl Iterator<Item = &ProcessEntry> {
+        let mut seen = HashSet::from([id]);
+        let mut current = id;
+
+        core::iter::from_fn(move || {
+            let parent = self.parent(current)?;
+            current = parent.id;
+            seen.insert(parent.id).then_some(parent)
+        })
+    }
+
+    /// Returns an iterator over processes whose parents aren't in the tree.
+    pub fn roots(&self) -> impl Iterator<Item = &ProcessEntry> {
+        self.entries
+            .iter()
+            .filter(|e| e.parent_id == e.id || !self.index.contains_key(&e.parent_id))
+    }
+}
+
+impl OwnedProcess {
+    /// Returns the parent of the process.
+    /// # Errors
+    /// [`MfError::ProcessNotFound`] if the parent has exited.
+    pub fn parent(&self) -> crate::Result<ProcessEntry> {
+        ProcessTree::new()?
+            .parent(self.id())
+            .cloned()
+            .ok_or(MfError::ProcessNotFound)
+    }
+
+    /// Returns direct children of the process.
+    pub fn children(&self) -> crate::Result<Vec<ProcessEntry>> {
+        Ok(ProcessTree::new()?.children(self.id()).cloned().collect())
+    }
+
+    /// Returns children of the process, their children and so on.
+    /// Every child comes before its own children.
+    pub fn descendants(&self) -> crate::Result<Vec<ProcessEntry>> {
+        Ok(ProcessTree::new()?
+            .descendants(self.id())
+            .cloned()
+            .collect())
+    }
+}
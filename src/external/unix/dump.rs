@@ -0,0 +1,287 @@
+use super::{io_error, OwnedProcess};
+use crate::{
+    coredump::{
+        prstatus_pid_offset, prstatus_regs_offset, EM_386, EM_AARCH64, EM_ARM, EM_X86_64, ET_CORE,
+        NT_FILE, NT_PRSTATUS, PN_XNUM, PT_LOAD, PT_NOTE,
+    },
+    types::Protection,
+    MfError,
+};
+use std::{fs::File, os::unix::fs::FileExt, path::Path};
+
+/// Size of a chunk memory is copied in.
+const CHUNK: usize = 0x10_0000;
+
+/// Threads stopped with `ptrace` until dropped.
+struct Stopped(Vec<(u32, i32)>);
+
+impl Stopped {
+    fn ptrace(request: libc::c_uint, tid: u32, addr: usize, data: usize) -> crate::Result<()> {
+        unsafe {
+            if libc::ptrace(request, tid as libc::pid_t, addr, data) == -1 {
+                MfError::last()
+            } else {
+                Ok(())
+            }
+        }
+    }
+
+    /// Attaches to every thread, skipping the ones that exited meanwhile.
+    fn attach(tids: &[u32]) -> crate::Result<Self> {
+        let mut stopped = Self(vec![]);
+
+        for &tid in tids {
+            match Self::ptrace(libc::PTRACE_SEIZE, tid, 0, 0)
+                .and_then(|_| Self::ptrace(libc::PTRACE_INTERRUPT, tid, 0, 0))
+            {
+                Ok(_) => {}
+                Err(MfError::Errno(libc::ESRCH)) => continue,
+                Err(e) => return Err(e),
+            }
+
+            let mut status = 0;
+            loop {
+                if unsafe { libc::waitpid(tid as _, &mut status, libc::__WALL) } != -1 {
+                    break;
+                }
+                match MfError::last::<()>() {
+                    Err(MfError::Errno(libc::EINTR)) => continue,
+                    Err(e) => return Err(e),
+                    Ok(_) => unreachable!(),
+                }
+            }
+
+            if libc::WIFSTOPPED(status) {
+                // Signal that arrived before the interrupt has to be delivered after detaching.
+                let signal = match libc::WSTOPSIG(status) {
+                    libc::SIGTRAP if status >> 16 == libc::PTRACE_EVENT_STOP => 0,
+                    signal => signal,
+                };
+                stopped.0.push((tid, signal));
+            }
+        }
+
+        Ok(stopped)
+    }
+
+    /// Returns general purpose registers of the thread.
+    fn registers(tid: u32) -> crate::Result<Vec<u8>> {
+        let mut buf = vec![0u8; 0x400];
+        let mut iov = libc::iovec {
+            iov_base: buf.as_mut_ptr().cast(),
+            iov_len: buf.len(),
+        };
+
+        Self::ptrace(
+            libc::PTRACE_GETREGSET,
+            tid,
+            NT_PRSTATUS as usize,
+            &mut iov as *mut _ as usize,
+        )?;
+        buf.truncate(iov.iov_len);
+        Ok(buf)
+    }
+}
+
+impl Drop for Stopped {
+    fn drop(&mut self) {
+        for &(tid, signal) in &self.0 {
+            _ = Self::ptrace(libc::PTRACE_DETACH, tid, 0, signal as usize);
+        }
+    }
+}
+
+/// Appends a pointer sized word.
+fn push_word(out: &mut Vec<u8>, value: u64, ptr_size: usize) {
+    if ptr_size == 8 {
+        out.extend(value.to_ne_bytes());
+    } else {
+        out.extend((value as u32).to_ne_bytes());
+    }
+}
+
+/// Appends a note named `CORE`.
+fn push_note(out: &mut Vec<u8>, kind: u32, desc: &[u8]) {
+    for word in [5, desc.len() as u32, kind] {
+        out.extend(word.to_ne_bytes());
+    }
+    out.extend(b"CORE\0\0\0\0");
+    out.extend(desc);
+    out.resize(out.len().next_multiple_of(4), 0);
+}
+
+/// ELF machine of a process running on the current architecture.
+const fn machine(ptr_size: usize) -> u16 {
+    let wide = ptr_size == 8;
+    if cfg!(any(target_arch = "x86_64", target_arch = "x86")) {
+        if wide {
+            EM_X86_64
+        } else {
+            EM_386
+        }
+    } else if cfg!(any(target_arch = "aarch64", target_arch = "arm")) {
+        if wide {
+            EM_AARCH64
+        } else {
+            EM_ARM
+        }
+    } else {
+        0
+    }
+}
+
+impl OwnedProcess {
+    /// Writes an ELF core dump of the process to `path`, see [`crate::CoreDump`].
+    /// # Behavior
+    /// * Every thread is stopped with `ptrace` while the dump is written and its
+    ///   registers are saved. Threads of the current process can't be traced, so its
+    ///   dump is taken while it runs and has no registers.
+    /// * Readable mappings are stored whole, bytes that can't be read are stored as zeroes.
+    /// * 32-bit processes are dumped as ELF32 files.
+    /// # Errors
+    /// [`MfError::ProcessDied`] if the process exits while it's being dumped.
+    /// ```no_run
+    /// # use memflex::{external::find_process_by_name, CoreDump, MemorySource};
+    /// let p = find_process_by_name("game")?;
+    /// p.dump_core("game.core")?;
+    ///
+    /// let core = CoreDump::open("game.core")?;
+    /// println!("{:X?}", core.threads()[0].instruction_pointer());
+    /// # Ok::<_, memflex::MfError>(())
+    /// ```
+    pub fn dump_core(&self, path: impl AsRef<Path>) -> crate::Result<()> {
+        let w = self.pointer_size()?;
+
+        let mut tids = self
+            .checked(std::fs::read_dir(format!("/proc/{}/task", self.pid)).map_err(io_error))?
+            .filter_map(|e| e.ok()?.file_name().to_str()?.parse::<u32>().ok())
+            .collect::<Vec<_>>();
+        // Main thread goes first.
+        tids.sort_unstable_by_key(|t| (*t != self.pid, *t));
+
+        let stopped = if self.pid == std::process::id() {
+            None
+        } else {
+            Some(Stopped::attach(&tids)?)
+        };
+
+        let mut notes = vec![];
+        let threads = match &stopped {
+            Some(s) => s.0.clone(),
+            None => tids.into_iter().map(|tid| (tid, 0)).collect(),
+        };
+        for (tid, signal) in threads {
+            let mut desc = vec![0; prstatus_regs_offset(w)];
+            desc[12..14].copy_from_slice(&(signal as u16).to_ne_bytes());
+            desc[prstatus_pid_offset(w)..][..4].copy_from_slice(&tid.to_ne_bytes());
+            if stopped.is_some() {
+                desc.extend(Stopped::registers(tid)?);
+            }
+            desc.extend(vec![0; w]);
+            push_note(&mut notes, NT_PRSTATUS, &desc);
+        }
+
+        let maps = self.mappings()?;
+        let page_size = unsafe { libc::sysconf(libc::_SC_PAGESIZE) } as u64;
+        let files = maps.iter().filter(|m| m.path.is_some()).collect::<Vec<_>>();
+
+        let mut desc = vec![];
+        push_word(&mut desc, files.len() as u64, w);
+        push_word(&mut desc, page_size, w);
+        for m in &files {
+            push_word(&mut desc, m.from as u64, w);
+            push_word(&mut desc, m.to as u64, w);
+            push_word(&mut desc, m.offset / page_size, w);
+        }
+        for m in &files {
+            desc.extend(m.path.as_ref().unwrap().as_bytes());
+            desc.push(0);
+        }
+        push_note(&mut notes, NT_FILE, &desc);
+
+        let (ehsize, phentsize, shentsize) = if w == 8 { (64, 56, 64) } else { (52, 32, 40) };
+        let phnum = maps.len() + 1;
+        // Too many program headers for `e_phnum`, the amount is stored in the first section.
+        let xnum = phnum >= PN_XNUM as usize;
+        let phoff = ehsize;
+        let shoff = phoff + phnum * phentsize;
+        let notes_offset = shoff + if xnum { shentsize } else { 0 };
+
+        let file = File::create(path)?;
+        file.write_all_at(&notes, notes_offset as u64)?;
+
+        // (offset, stored) of every mapping.
+        let mut stored = vec![];
+        let mut offset = (notes_offset + notes.len()).next_multiple_of(0x1000);
+        let mut buf = vec![0; CHUNK];
+        for m in &maps {
+            let len = if m.prot.read() { m.to - m.from } else { 0 };
+            let mut done = 0;
+            while done < len {
+                let chunk = CHUNK.min(len - done);
+                // Pages that can't be read, e.g. past the end of a mapped file, are zeroed.
+                self.read_buf_partial(m.from + done, &mut buf[..chunk], 0)?;
+                file.write_all_at(&buf[..chunk], (offset + done) as u64)?;
+                done += chunk;
+            }
+
+            stored.push((offset, done));
+            offset = (offset + done).next_multiple_of(0x1000);
+        }
+        drop(stopped);
+
+        let mut headers = vec![];
+        headers.extend(b"\x7FELF");
+        headers.push(if w == 8 { 2 } else { 1 });
+        headers.push(if cfg!(target_endian = "little") { 1 } else { 2 });
+        headers.push(1);
+        headers.resize(16, 0);
+        headers.extend(ET_CORE.to_ne_bytes());
+        headers.extend(machine(w).to_ne_bytes());
+        headers.extend(1u32.to_ne_bytes());
+        push_word(&mut headers, 0, w);
+        push_word(&mut headers, phoff as u64, w);
+        push_word(&mut headers, if xnum { shoff as u64 } else { 0 }, w);
+        headers.extend(0u32.to_ne_bytes());
+        let e_phnum = if xnum { PN_XNUM } else { phnum as u16 };
+        for half in [ehsize as u16, phentsize as u16, e_phnum] {
+            headers.extend(half.to_ne_bytes());
+        }
+        for half in [shentsize as u16, xnum as u16, 0] {
+            headers.extend(half.to_ne_bytes());
+        }
+
+        let mut segments = vec![(PT_NOTE, 0, notes_offset, 0, notes.len(), notes.len())];
+        for (m, (offset, stored)) in maps.iter().zip(stored) {
+            let flags = [(Protection::R, 4), (Protection::W, 2), (Protection::X, 1)]
+                .into_iter()
+                .filter(|(p, _)| m.prot.contains(*p))
+                .fold(0u32, |acc, (_, bit)| acc | bit);
+            segments.push((PT_LOAD, flags, offset, m.from, stored, m.to - m.from));
+        }
+
+        for (kind, flags, offset, vaddr, filesz, memsz) in segments {
+            let align = if kind == PT_LOAD { 0x1000 } else { 4 };
+            headers.extend(kind.to_ne_bytes());
+            if w == 8 {
+                headers.extend(flags.to_ne_bytes());
+            }
+            for word in [offset, vaddr, 0, filesz, memsz] {
+                push_word(&mut headers, word as u64, w);
+            }
+            if w != 8 {
+                headers.extend(flags.to_ne_bytes());
+            }
+            push_word(&mut headers, align, w);
+        }
+
+        if xnum {
+            let mut section = vec![0; shentsize];
+            section[12 + 4 * w..][..4].copy_from_slice(&(phnum as u32).to_ne_bytes());
+            headers.extend(section);
+        }
+
+        file.write_all_at(&headers, 0)?;
+        Ok(())
+    }
+}
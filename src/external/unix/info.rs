@@ -0,0 +1,248 @@
+use super::OwnedProcess;
+use crate::MfError;
+use std::{
+    fs,
+    io::{self, Read},
+    path::{Path, PathBuf},
+    time::{Duration, SystemTime},
+};
+
+/// Scheduling state of a process, as reported by `/proc/<pid>/stat`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ProcessState {
+    /// `R`
+    Running,
+    /// `S`
+    Sleeping,
+    /// `D`, uninterruptible sleep.
+    DiskSleep,
+    /// `Z`
+    Zombie,
+    /// `T`
+    Stopped,
+    /// `t`
+    TracingStop,
+    /// `X`
+    Dead,
+    /// `I`
+    Idle,
+    /// Any other state character.
+    Other(char),
+}
+
+impl ProcessState {
+    fn from_char(c: char) -> Self {
+        match c {
+            'R' => Self::Running,
+            'S' => Self::Sleeping,
+            'D' => Self::DiskSleep,
+            'Z' => Self::Zombie,
+            'T' => Self::Stopped,
+            't' => Self::TracingStop,
+            'X' | 'x' => Self::Dead,
+            'I' => Self::Idle,
+            c => Self::Other(c),
+        }
+    }
+}
+
+/// Class of an ELF file, i.e. its pointer width.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ElfClass {
+    /// 32-bit executable.
+    Elf32,
+    /// 64-bit executable.
+    Elf64,
+}
+
+impl ElfClass {
+    /// Size of a pointer in bytes.
+    #[inline]
+    pub const fn pointer_size(&self) -> usize {
+        match self {
+            Self::Elf32 => 4,
+            Self::Elf64 => 8,
+        }
+    }
+}
+
+pub(crate) fn io_error(e: io::Error) -> MfError {
+    match e.raw_os_error() {
+        Some(libc::ENOENT | libc::ESRCH) | None => MfError::ProcessDied,
+        Some(errno) => MfError::Errno(errno),
+    }
+}
+
+/// Returns the value of the `key` line in the contents of `/proc/<pid>/status`.
+pub(crate) fn status_value<'a>(status: &'a str, key: &str) -> Option<&'a str> {
+    status.lines().find_map(|l| {
+        l.split_once(':')
+            .filter(|(k, _)| *k == key)
+            .map(|(_, v)| v.trim())
+    })
+}
+
+/// Parses the `NSpid` value, falling back to the id on kernels that don't report it.
+pub(crate) fn namespace_ids(id: u32, nspid: Option<&str>) -> Vec<u32> {
+    nspid
+        .and_then(|v| v.split_whitespace().map(|p| p.parse().ok()).collect())
+        .unwrap_or_else(|| vec![id])
+}
+
+/// Splits zero terminated list, e.g. `/proc/<pid>/cmdline`. Empty items are kept.
+fn split_zeros(buf: &[u8]) -> impl Iterator<Item = String> + '_ {
+    let buf = buf.strip_suffix(&[0]).unwrap_or(buf);
+    (!buf.is_empty())
+        .then(|| buf.split(|b| *b == 0))
+        .into_iter()
+        .flatten()
+        .map(|s| String::from_utf8_lossy(s).into_owned())
+}
+
+impl OwnedProcess {
+    /// Returns fields of `/proc/<pid>/stat` that follow the process name.
+    /// First returned item is the state, which is field number 3 in `man proc`.
+    fn stat_fields(&self) -> crate::Result<Vec<String>> {
+        let stat = String::from_utf8_lossy(&self.proc_file("stat")?).into_owned();
+
+        // Process name can contain spaces and parentheses.
+        let (_, tail) = stat.rsplit_once(')').ok_or(MfError::ProcessDied)?;
+        Ok(tail.split_whitespace().map(str::to_owned).collect())
+    }
+
+    fn stat_field(&self, field: usize) -> crate::Result<String> {
+        self.stat_fields()?
+            .into_iter()
+            .nth(field - 3)
+            .ok_or(MfError::ProcessDied)
+    }
+
+    /// Returns the values of the `key` line in `/proc/<pid>/status`.
+    pub(crate) fn status_field(&self, key: &str) -> crate::Result<String> {
+        let status = String::from_utf8_lossy(&self.proc_file("status")?).into_owned();
+
+        status_value(&status, key)
+            .map(str::to_owned)
+            .ok_or(MfError::ProcessDied)
+    }
+
+    /// Returns the command line arguments the process was started with, including `argv[0]`.
+    pub fn cmdline(&self) -> crate::Result<Vec<String>> {
+        Ok(split_zeros(&self.proc_file("cmdline")?).collect())
+    }
+
+    /// Returns the initial environment of the process as `(key, value)` pairs.
+    /// # Note
+    /// Changes made by the process with `setenv` are not reflected.
+    pub fn environ(&self) -> crate::Result<Vec<(String, String)>> {
+        Ok(split_zeros(&self.proc_file("environ")?)
+            .map(|kv| match kv.split_once('=') {
+                Some((k, v)) => (k.to_owned(), v.to_owned()),
+                None => (kv, String::new()),
+            })
+            .collect())
+    }
+
+    /// Returns current working directory of the process.
+    pub fn cwd(&self) -> crate::Result<String> {
+        Ok(self.proc_link("cwd")?.to_string_lossy().into_owned())
+    }
+
+    /// Returns real user id of the process.
+    pub fn uid(&self) -> crate::Result<u32> {
+        self.status_field("Uid")?
+            .split_whitespace()
+            .next()
+            .and_then(|v| v.parse().ok())
+            .ok_or(MfError::ProcessDied)
+    }
+
+    /// Returns real group id of the process.
+    pub fn gid(&self) -> crate::Result<u32> {
+        self.status_field("Gid")?
+            .split_whitespace()
+            .next()
+            .and_then(|v| v.parse().ok())
+            .ok_or(MfError::ProcessDied)
+    }
+
+    /// Returns current scheduling state of the process.
+    pub fn state(&self) -> crate::Result<ProcessState> {
+        self.stat_field(3)?
+            .chars()
+            .next()
+            .map(ProcessState::from_char)
+            .ok_or(MfError::ProcessDied)
+    }
+
+    /// Returns the time the process was started at.
+    pub fn start_time(&self) -> crate::Result<SystemTime> {
+        let ticks = self
+            .stat_field(22)?
+            .parse::<u64>()
+            .map_err(|_| MfError::ProcessDied)?;
+
+        let boot = fs::read_to_string("/proc/stat")
+            .map_err(io_error)?
+            .lines()
+            .find_map(|l| l.strip_prefix("btime "))
+            .and_then(|v| v.trim().parse::<u64>().ok())
+            .ok_or(MfError::ProcessDied)?;
+
+        let hz = unsafe { libc::sysconf(libc::_SC_CLK_TCK) }.max(1) as u64;
+
+        Ok(SystemTime::UNIX_EPOCH
+            + Duration::from_secs(boot)
+            + Duration::from_millis(ticks * 1000 / hz))
+    }
+
+    /// Returns the number of threads in the process.
+    pub fn thread_count(&self) -> crate::Result<usize> {
+        self.stat_field(20)?
+            .parse()
+            .map_err(|_| MfError::ProcessDied)
+    }
+
+    /// Returns ids of the process in nested PID namespaces, starting with the namespace
+    /// of the current process and ending with the one the process itself lives in.
+    /// # Behavior
+    /// Only the id is returned on kernels that don't report `NSpid`.
+    /// ```no_run
+    /// # use memflex::external::find_process_by_name;
+    /// // Game running in a container, `[48213, 7]` from the host.
+    /// let game = find_process_by_name("game")?;
+    /// let inner = game.namespace_ids()?.last().copied();
+    /// # Ok::<_, memflex::MfError>(())
+    /// ```
+    pub fn namespace_ids(&self) -> crate::Result<Vec<u32>> {
+        let status = String::from_utf8_lossy(&self.proc_file("status")?).into_owned();
+        Ok(namespace_ids(self.pid, status_value(&status, "NSpid")))
+    }
+
+    /// Converts a path in the process's mount namespace, e.g. of its executable or
+    /// a module, to a path that can be opened from the current process.
+    /// # Behavior
+    /// The path goes through `/proc/<pid>/root`, so it works for processes in containers
+    /// and chroots. Absolute symlinks inside the process's root are still resolved
+    /// against the root of the current process.
+    pub fn resolve_path(&self, path: impl AsRef<Path>) -> PathBuf {
+        let path = path.as_ref();
+        Path::new(&format!("/proc/{}/root", self.pid)).join(path.strip_prefix("/").unwrap_or(path))
+    }
+
+    /// Returns the class of the process's executable.
+    pub fn elf_class(&self) -> crate::Result<ElfClass> {
+        let mut ident = [0; 5];
+        self.checked(
+            fs::File::open(format!("/proc/{}/exe", self.pid))
+                .and_then(|mut f| f.read_exact(&mut ident))
+                .map_err(io_error),
+        )?;
+
+        match ident {
+            [0x7F, b'E', b'L', b'F', 1] => Ok(ElfClass::Elf32),
+            [0x7F, b'E', b'L', b'F', 2] => Ok(ElfClass::Elf64),
+            _ => Err(MfError::InvalidElf),
+        }
+    }
+}
@@ -1,2 +1,12 @@
 mod process;
 pub use process::*;
+mod info;
+pub use info::*;
+mod dump;
+mod smaps;
+#[cfg(all(target_os = "linux", target_arch = "x86_64"))]
+mod spawn;
+pub use smaps::*;
+mod string;
+#[cfg(all(target_os = "linux", target_arch = "x86_64"))]
+pub use spawn::*;
@@ -1,45 +1,172 @@
+use super::{info::namespace_ids, info::status_value, io_error};
 use crate::{
-    external::{MemoryRegion, ProcessEntry},
+    external::{MemoryRegion, ProcessEntry, Wait},
     types::{ModuleInfoWithName, Protection},
-    Matcher, MfError,
-};
-use core::{
-    mem::{size_of, MaybeUninit},
-    slice::{from_raw_parts, from_raw_parts_mut},
+    Matcher, MemorySource, MfError,
 };
+use core::{mem::size_of, ops::Range, slice::from_raw_parts};
 use std::{
     collections::HashMap,
     fs,
+    os::fd::{AsFd, AsRawFd, BorrowedFd, FromRawFd, OwnedFd, RawFd},
     path::{Path, PathBuf},
+    sync::OnceLock,
+    time::{Duration, Instant},
 };
 
+/// Mapping parsed from `/proc/<pid>/maps`.
+pub(crate) struct Mapping {
+    pub from: usize,
+    pub to: usize,
+    pub prot: Protection,
+    pub offset: u64,
+    /// Path of the mapped file in the process's mount namespace.
+    pub path: Option<String>,
+}
+
 /// Represents a single process in the system.
 /// # Details
 /// There is no such concept as 'owned' procses in unix. (i think).
 /// The name is the same as on windows to reduce the hasle of cross-platform code.
+/// # Pidfd
+/// If the kernel supports it, the process is also referred to by a `pidfd`.
+/// Once the original process exits, every operation returns [`MfError::ProcessDied`]
+/// even if its id was reused by another process.
 #[derive(Debug)]
-#[repr(transparent)]
-pub struct OwnedProcess(pub(crate) u32);
+pub struct OwnedProcess {
+    pub(crate) pid: u32,
+    pub(crate) pidfd: Option<OwnedFd>,
+    ptr_size: OnceLock<usize>,
+}
 
 impl OwnedProcess {
+    /// Creates a process from its id, trying to open a `pidfd` for it.
+    pub(crate) fn open(pid: u32) -> crate::Result<Self> {
+        let fd = unsafe { libc::syscall(libc::SYS_pidfd_open, pid as libc::pid_t, 0) };
+
+        if fd >= 0 {
+            Ok(Self {
+                pid,
+                pidfd: Some(unsafe { OwnedFd::from_raw_fd(fd as _) }),
+                ptr_size: OnceLock::new(),
+            })
+        } else {
+            match MfError::last::<()>() {
+                Err(MfError::Errno(libc::ESRCH)) => Err(MfError::ProcessNotFound),
+                // Old kernel or not allowed, fallback to plain pid.
+                _ => Ok(Self::from_pid(pid)),
+            }
+        }
+    }
+
+    /// Creates a process from its id without a `pidfd`.
+    /// Such process can not detect if its id was reused.
+    pub fn from_pid(pid: u32) -> Self {
+        Self {
+            pid,
+            pidfd: None,
+            ptr_size: OnceLock::new(),
+        }
+    }
+
     /// Returns the id of the process.
     #[inline]
     pub fn id(&self) -> u32 {
-        self.0
+        self.pid
+    }
+
+    /// Returns the `pidfd` referring to the process, if it was opened.
+    #[inline]
+    pub fn pidfd(&self) -> Option<BorrowedFd<'_>> {
+        self.pidfd.as_ref().map(|fd| fd.as_fd())
+    }
+
+    /// Checks if the process is still running.
+    /// # Behavior
+    /// Zombie processes are considered dead.
+    /// Without `pidfd` there is no way to tell if the process id was reused.
+    /// # Errors
+    /// If the `pidfd` can't be polled or `/proc/<pid>/stat` can't be read for a reason
+    /// other than the process being gone.
+    pub fn is_alive(&self) -> crate::Result<bool> {
+        match &self.pidfd {
+            Some(fd) => Ok(!poll_readable(fd.as_raw_fd(), Some(Duration::ZERO))?),
+            None => {
+                match fs::read_to_string(format!("/proc/{}/stat", self.pid)).map_err(io_error) {
+                    Ok(s) => Ok(s
+                        .rsplit_once(')')
+                        .is_some_and(|(_, s)| !s.trim_start().starts_with(['Z', 'X']))),
+                    Err(MfError::ProcessDied) => Ok(false),
+                    Err(e) => Err(e),
+                }
+            }
+        }
+    }
+
+    /// Blocks until the process exits or `timeout` elapses.
+    /// Returns `true` if the process exited and `false` on timeout.
+    /// `None` means no timeout.
+    pub fn wait_exit(&self, timeout: Option<Duration>) -> crate::Result<bool> {
+        match &self.pidfd {
+            Some(fd) => poll_readable(fd.as_raw_fd(), timeout),
+            None => {
+                const INTERVAL: Duration = Duration::from_millis(10);
+
+                let start = Instant::now();
+                while self.is_alive()? {
+                    if timeout.is_some_and(|t| start.elapsed() >= t) {
+                        return Ok(false);
+                    }
+
+                    std::thread::sleep(INTERVAL);
+                }
+
+                Ok(true)
+            }
+        }
+    }
+
+    /// Maps the result of an operation on the process, making sure it was performed
+    /// on the original process.
+    pub(crate) fn checked<T>(&self, result: crate::Result<T>) -> crate::Result<T> {
+        self.ensure_alive()?;
+
+        match result {
+            Err(MfError::Errno(libc::ESRCH)) => Err(MfError::ProcessDied),
+            r => r,
+        }
+    }
+
+    /// Fails if the process behind the pidfd exited, so its pid may have been reused.
+    pub(crate) fn ensure_alive(&self) -> crate::Result<()> {
+        if self.pidfd.is_some() && !self.is_alive()? {
+            Err(MfError::ProcessDied)
+        } else {
+            Ok(())
+        }
+    }
+
+    /// Reads a link in `/proc/<pid>/`
+    pub(crate) fn proc_link(&self, file: &str) -> crate::Result<PathBuf> {
+        self.checked(fs::read_link(format!("/proc/{}/{file}", self.pid)).map_err(io_error))
+    }
+
+    /// Reads a file in `/proc/<pid>/`
+    pub(crate) fn proc_file(&self, file: &str) -> crate::Result<Vec<u8>> {
+        self.checked(fs::read(format!("/proc/{}/{file}", self.pid)).map_err(io_error))
     }
 
     /// Returns full path to the process.
+    /// # Behavior
+    /// The path is in the process's mount namespace, see [`OwnedProcess::resolve_path`].
     pub fn path(&self) -> crate::Result<String> {
-        Ok(fs::read_link(format!("/proc/{}/exe", self.0))
-            .map_err(|_| MfError::ProcessDied)?
-            .to_string_lossy()
-            .into_owned())
+        Ok(self.proc_link("exe")?.to_string_lossy().into_owned())
     }
 
     /// Returns the name of the process
     pub fn name(&self) -> crate::Result<String> {
-        Ok(fs::read_link(format!("/proc/{}/exe", self.0))
-            .map_err(|_| MfError::ProcessDied)?
+        Ok(self
+            .proc_link("exe")?
             .file_name()
             .map(|n| n.to_string_lossy().into_owned())
             .unwrap_or_default())
@@ -47,9 +174,10 @@ impl OwnedProcess {
 
     /// Reads process memory, returning amount of bytes read.
     pub fn read_buf(&self, address: usize, buf: &mut [u8]) -> crate::Result<usize> {
-        unsafe {
+        // Checked after the read, data from a reused pid is discarded.
+        let result = unsafe {
             let read = libc::process_vm_readv(
-                self.0 as _,
+                self.pid as _,
                 &libc::iovec {
                     iov_base: buf.as_mut_ptr() as _,
                     iov_len: buf.len(),
@@ -68,49 +196,36 @@ impl OwnedProcess {
             } else {
                 Ok(read as usize)
             }
-        }
-    }
+        };
 
-    /// Reads a value of type `T` at `address`.
-    pub fn read<T>(&self, address: usize) -> crate::Result<T> {
-        unsafe {
-            let mut buf: MaybeUninit<T> = MaybeUninit::uninit();
-            self.read_buf(
-                address,
-                from_raw_parts_mut(buf.as_mut_ptr().cast::<u8>() as _, size_of::<T>()),
-            )?;
-            Ok(buf.assume_init())
-        }
+        self.checked(result)
     }
 
-    /// Reads zero terminated string at `address`.
-    pub fn read_str(&self, address: usize) -> crate::Result<String> {
-        const STRIDE: usize = 4;
-
-        let mut out = vec![];
-        let mut offset = 0;
-
-        loop {
-            let buf = self.read::<[u8; STRIDE]>(address + offset)?;
-
-            if let Some(i) = buf.iter().position(|b| *b == 0) {
-                out.extend_from_slice(&buf[..i]);
-                break;
-            } else {
-                out.extend_from_slice(&buf);
-            }
-
-            offset += STRIDE
-        }
+    /// Reads process memory into the whole buffer, replacing bytes that can't be read with
+    /// `fill`. Returns address ranges that were read, see [`MemorySource::read_buf_partial`].
+    pub fn read_buf_partial(
+        &self,
+        address: usize,
+        buf: &mut [u8],
+        fill: u8,
+    ) -> crate::Result<Vec<Range<usize>>> {
+        MemorySource::read_buf_partial(self, address, buf, fill)
+    }
 
-        String::from_utf8(out).map_err(|_| MfError::InvalidString)
+    /// Reads a value of type `T` at `address`.
+    /// # Errors
+    /// [`MfError::InvalidAddress`] if only part of the value could be read.
+    pub fn read<T: Copy>(&self, address: usize) -> crate::Result<T> {
+        MemorySource::read(self, address)
     }
 
     /// Writes process memory, returning amount of bytes written.
     pub fn write_buf(&self, address: usize, buf: &[u8]) -> crate::Result<usize> {
-        unsafe {
+        // Checked before the write, a reused pid must not get the data.
+        self.ensure_alive()?;
+        let result = unsafe {
             let written = libc::process_vm_writev(
-                self.0 as _,
+                self.pid as _,
                 &libc::iovec {
                     iov_base: buf.as_ptr() as _,
                     iov_len: buf.len(),
@@ -129,7 +244,9 @@ impl OwnedProcess {
             } else {
                 Ok(written as usize)
             }
-        }
+        };
+
+        self.checked(result)
     }
 
     /// Writes `value` at `address` in the process's memory, returning amount of bytes written.
@@ -143,9 +260,12 @@ impl OwnedProcess {
     }
 
     /// Returns an iterator over process's modules.
+    /// # Behavior
+    /// * Module's size includes anonymous `.bss` mapping that directly follows it.
+    /// * Only the file name of the module is kept, use [`OwnedProcess::module_path`]
+    ///   to open the module's file from the current process.
     pub fn modules(&self) -> crate::Result<impl Iterator<Item = ModuleInfoWithName>> {
-        let s = fs::read_to_string(format!("/proc/{}/maps", self.0))
-            .map_err(|_| MfError::ProcessDied)?;
+        let s = String::from_utf8_lossy(&self.proc_file("maps")?).into_owned();
 
         struct ModRange {
             from: usize,
@@ -153,17 +273,15 @@ impl OwnedProcess {
         }
 
         let mut maps: HashMap<String, ModRange> = HashMap::new();
+        // Module that the previous line belonged to.
+        let mut last: Option<String> = None;
 
         for l in s.lines() {
-            let map = l
-                .split_whitespace()
-                .filter(|v| !v.is_empty())
-                .collect::<Vec<_>>();
-            if map.len() != 6 {
+            let map = l.splitn(6, ' ').collect::<Vec<_>>();
+            if map.len() < 5 {
                 continue;
             }
 
-            let libname = map[5];
             let convert = |s: &str| usize::from_str_radix(s, 16).unwrap();
 
             let (from, to) = map[0]
@@ -171,7 +289,22 @@ impl OwnedProcess {
                 .map(|(from, to)| (convert(from), convert(to)))
                 .unwrap();
 
-            if fs::metadata(libname).is_ok() {
+            // Path is padded with spaces and can contain them.
+            let libname = map.get(5).map_or("", |p| p.trim_start());
+            if libname.is_empty() {
+                // Anonymous mapping right after the module is its `.bss`.
+                if let Some(ent) = last.take().and_then(|n| maps.get_mut(&n)) {
+                    if ent.to == from {
+                        ent.to = to;
+                    }
+                }
+                continue;
+            }
+
+            last = None;
+
+            // Module paths are relative to the process's root if it runs in a container.
+            if fs::metadata(self.resolve_path(libname)).is_ok() {
                 let ent = maps
                     .entry(libname.to_owned())
                     .or_insert_with(|| ModRange { from, to });
@@ -181,6 +314,7 @@ impl OwnedProcess {
                 } else if to > ent.to {
                     ent.to = to;
                 }
+                last = Some(libname.to_owned());
             }
         }
 
@@ -203,6 +337,38 @@ impl OwnedProcess {
             .ok_or(MfError::ModuleNotFound)
     }
 
+    /// Returns the path to the file of the module that can be opened from the current process.
+    /// # Case
+    /// Search is done case insensetive.
+    /// # Behavior
+    /// The path is resolved with [`OwnedProcess::resolve_path`].
+    pub fn module_path(&self, name: &str) -> crate::Result<PathBuf> {
+        self.mappings()?
+            .into_iter()
+            .filter_map(|m| m.path)
+            .find(|p| {
+                Path::new(p)
+                    .file_name()
+                    .is_some_and(|n| n.to_string_lossy().eq_ignore_ascii_case(name))
+            })
+            .map(|p| self.resolve_path(p))
+            .ok_or(MfError::ModuleNotFound)
+    }
+
+    /// Waits until the module is loaded in the process.
+    /// # Errors
+    /// * [`MfError::ModuleNotFound`] if the module was not loaded before the timeout.
+    /// * [`MfError::ProcessDied`] if the process exited while waiting.
+    pub fn wait_for_module(
+        &self,
+        name: &str,
+        wait: impl Into<Wait>,
+    ) -> crate::Result<ModuleInfoWithName> {
+        wait.into()
+            .poll(|| Ok(self.modules()?.find(|m| m.name.eq_ignore_ascii_case(name))))?
+            .ok_or(MfError::ModuleNotFound)
+    }
+
     /// Finds all occurences of the pattern in a given range.
     // TODO: Can be optimized
     pub fn find_pattern<'a>(
@@ -211,30 +377,7 @@ impl OwnedProcess {
         start: usize,
         len: usize,
     ) -> impl Iterator<Item = usize> + 'a {
-        let mut offset = 0;
-        let mut buf = vec![0; pat.len()];
-
-        std::iter::from_fn(move || {
-            loop {
-                if self.read_buf(start + offset, &mut buf[..]).is_err() {
-                    return None;
-                }
-
-                if pat.matches(&buf[..]) {
-                    break;
-                }
-
-                offset += 1;
-
-                if offset >= len {
-                    return None;
-                }
-            }
-
-            offset += 1;
-            Some(start + offset - 1)
-        })
-        .fuse()
+        MemorySource::find_pattern(self, pat, start, len)
     }
 
     /// Searches for a pattern in the specified module.
@@ -250,8 +393,7 @@ impl OwnedProcess {
 
     /// Returns an iterator over mapped regions in the process.
     pub fn maps(&self) -> crate::Result<Vec<MemoryRegion>> {
-        Ok(fs::read_to_string(format!("/proc/{}/maps", self.0))
-            .map_err(|_| MfError::ProcessDied)?
+        Ok(String::from_utf8_lossy(&self.proc_file("maps")?)
             .lines()
             .map(|l| {
                 let mut iter = l.split(' ');
@@ -267,6 +409,31 @@ impl OwnedProcess {
             .collect())
     }
 
+    /// Parses `/proc/<pid>/maps` including offsets and paths of mapped files.
+    pub(crate) fn mappings(&self) -> crate::Result<Vec<Mapping>> {
+        Ok(String::from_utf8_lossy(&self.proc_file("maps")?)
+            .lines()
+            .filter_map(|l| {
+                let mut iter = l.splitn(6, ' ');
+                let (from, to) = iter.next()?.split_once('-')?;
+                let prot = Protection::parse(iter.next()?.get(0..3)?);
+                let offset = u64::from_str_radix(iter.next()?, 16).ok()?;
+                let path = iter
+                    .nth(2)
+                    .map(str::trim_start)
+                    .filter(|p| p.starts_with('/'));
+
+                Some(Mapping {
+                    from: usize::from_str_radix(from, 16).ok()?,
+                    to: usize::from_str_radix(to, 16).ok()?,
+                    prot,
+                    offset,
+                    path: path.map(str::to_owned),
+                })
+            })
+            .collect())
+    }
+
     /// Queryies protection for the specified address.
     /// `None` if no mappings were found for this address.
     pub fn query(&self, address: usize) -> crate::Result<Option<Protection>> {
@@ -277,79 +444,92 @@ impl OwnedProcess {
             .map(|r| r.prot))
     }
 
-    /// Resolves multilevel pointer
-    pub fn resolve_multilevel(&self, mut base: usize, offsets: &[usize]) -> crate::Result<usize> {
-        for (i, &o) in offsets.iter().enumerate() {
-            if i != offsets.len() - 1 {
-                base = self.read(base + o)?;
-            } else {
-                base += o;
-            }
+    /// Returns the size of a pointer in the process, `4` for 32-bit processes.
+    /// # Behavior
+    /// Detected from the ELF class of the executable on first call.
+    pub fn pointer_size(&self) -> crate::Result<usize> {
+        if let Some(size) = self.ptr_size.get() {
+            return Ok(*size);
         }
 
-        Ok(base)
+        let size = self.elf_class()?.pointer_size();
+        Ok(*self.ptr_size.get_or_init(|| size))
+    }
+
+    /// Reads a pointer of the process's width, see [`OwnedProcess::pointer_size`].
+    pub fn read_pointer(&self, address: usize) -> crate::Result<usize> {
+        MemorySource::read_pointer(self, address)
+    }
+
+    /// Resolves multilevel pointer
+    /// # Behavior
+    /// Pointers are read with the process's width, see [`OwnedProcess::read_pointer`].
+    pub fn resolve_multilevel(&self, base: usize, offsets: &[usize]) -> crate::Result<usize> {
+        MemorySource::resolve_multilevel(self, base, offsets)
     }
 }
 
 /// Iterator over all processes in the system.
-pub struct ProcessIterator(Box<dyn Iterator<Item = ProcessEntry>>);
+/// # Unix
+/// Yields an error for a process whose details can't be read.
+/// Processes that exit during the iteration are skipped.
+pub struct ProcessIterator(fs::ReadDir);
 
 impl ProcessIterator {
     /// Creates new iterator over all processes in the system.
-    /// # Unix
-    /// Always returns Ok(I).
     pub fn new() -> crate::Result<Self> {
-        fn get_parent_id(proc: &Path) -> u32 {
-            let status = fs::read_to_string(proc.join("status")).unwrap();
-
-            let parent_id = status
-                .lines()
-                .find_map(|l: &str| {
-                    if l.starts_with("PPid:") {
-                        l.split_once(':').map(|(_, tail)| tail.trim().to_owned())
-                    } else {
-                        None
-                    }
-                })
-                .and_then(|p| p.parse::<u32>().ok())
-                .unwrap();
-
-            parent_id
-        }
-
-        let iter = fs::read_dir("/proc")
-            .unwrap()
-            .flatten()
-            .filter_map(|de| Some((de.file_name().to_str()?.parse::<u32>().ok()?, de)))
-            .filter_map(|(id, de)| {
-                let entry = de.path();
-
-                let path = fs::read_link(entry.join("exe")).ok()?;
-                let name = path.file_name()?.to_str()?.to_owned();
-                let parent_id = get_parent_id(&entry);
-
-                Some(ProcessEntry {
-                    id,
-                    name,
-                    parent_id,
-                })
-            });
+        Ok(Self(fs::read_dir("/proc")?))
+    }
 
-        Ok(Self(Box::new(iter)))
+    /// Reads the entry of the process, name is taken from `comm` if `exe` can't be read.
+    fn entry(id: u32) -> crate::Result<ProcessEntry> {
+        let process = OwnedProcess::from_pid(id);
+
+        let status = String::from_utf8_lossy(&process.proc_file("status")?).into_owned();
+        let parent_id = status_value(&status, "PPid")
+            .and_then(|v| v.parse().ok())
+            .ok_or(MfError::ProcessDied)?;
+        let namespace_ids = namespace_ids(id, status_value(&status, "NSpid"));
+        let path = process.proc_link("exe").ok();
+        let name = match path.as_ref().and_then(|p| p.file_name()) {
+            Some(name) => name.to_string_lossy().into_owned(),
+            None => String::from_utf8_lossy(&process.proc_file("comm")?)
+                .trim_end_matches('\n')
+                .to_owned(),
+        };
+
+        Ok(ProcessEntry {
+            id,
+            name,
+            parent_id,
+            path: path.map(|p| p.to_string_lossy().into_owned()),
+            namespace_ids,
+        })
     }
 }
 
 impl Iterator for ProcessIterator {
-    type Item = ProcessEntry;
+    type Item = crate::Result<ProcessEntry>;
 
     fn next(&mut self) -> Option<Self::Item> {
-        self.0.next()
+        loop {
+            let id = match self.0.next()? {
+                Ok(de) => de.file_name().to_str().and_then(|n| n.parse::<u32>().ok()),
+                Err(e) => return Some(Err(e.into())),
+            };
+
+            match id.map(Self::entry) {
+                None | Some(Err(MfError::ProcessDied)) => continue,
+                entry => return entry,
+            }
+        }
     }
 }
 
 /// Searches for the specified process by its name.
 pub fn find_process_by_name(name: &str) -> crate::Result<OwnedProcess> {
     ProcessIterator::new()?
+        .flatten()
         .find_map(|pe| {
             if pe.name.eq_ignore_ascii_case(name) {
                 Some(pe.open())
@@ -360,11 +540,62 @@ pub fn find_process_by_name(name: &str) -> crate::Result<OwnedProcess> {
         .ok_or(MfError::ProcessNotFound)?
 }
 
+/// Waits until the process with the specified name is started.
+/// # Errors
+/// [`MfError::ProcessNotFound`] if the process was not found before the timeout.
+/// ```no_run
+/// # use memflex::external::{wait_for_process, Wait};
+/// # use std::time::Duration;
+/// let game = wait_for_process("game", Duration::from_secs(60))?;
+/// let engine = game.wait_for_module("libengine.so", Wait::forever())?;
+/// # Ok::<_, memflex::MfError>(())
+/// ```
+pub fn wait_for_process(name: &str, wait: impl Into<Wait>) -> crate::Result<OwnedProcess> {
+    wait.into()
+        .poll(|| match find_process_by_name(name) {
+            Err(MfError::ProcessNotFound) => Ok(None),
+            r => r.map(Some),
+        })?
+        .ok_or(MfError::ProcessNotFound)
+}
+
 /// Searches for the specified process by its id.
 pub fn find_process_by_id(id: u32) -> crate::Result<OwnedProcess> {
     if fs::metadata(format!("/proc/{id}")).is_err() {
         return Err(MfError::ProcessNotFound);
     }
 
-    Ok(OwnedProcess(id))
+    OwnedProcess::open(id)
+}
+
+/// Polls `fd` for readability, returns `true` if it's readable before `timeout` elapses.
+/// `None` means no timeout.
+fn poll_readable(fd: RawFd, timeout: Option<Duration>) -> crate::Result<bool> {
+    let mut pfd = libc::pollfd {
+        fd,
+        events: libc::POLLIN,
+        revents: 0,
+    };
+    // Too long timeouts are treated as no timeout.
+    let deadline = timeout.and_then(|t| Instant::now().checked_add(t));
+
+    loop {
+        // Rounded up, so short timeouts don't turn into a non-blocking poll.
+        let timeout = deadline
+            .map(|d| {
+                let left = d.saturating_duration_since(Instant::now());
+                left.as_nanos().div_ceil(1_000_000).min(i32::MAX as u128) as i32
+            })
+            .unwrap_or(-1);
+
+        let n = unsafe { libc::poll(&mut pfd, 1, timeout) };
+        if n != -1 {
+            return Ok(n > 0);
+        }
+
+        match MfError::last() {
+            Err(MfError::Errno(libc::EINTR)) => continue,
+            r => return r,
+        }
+    }
 }
@@ -0,0 +1,216 @@
+use super::OwnedProcess;
+use crate::{external::MemoryRegion, types::Protection};
+use core::{
+    iter::Sum,
+    ops::{Add, AddAssign},
+};
+use std::{collections::HashMap, path::Path};
+
+/// Memory usage figures from `/proc/<pid>/smaps`, in bytes.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct MemoryUsage {
+    /// Resident in physical memory.
+    pub rss: usize,
+    /// Proportional share of resident memory, pages shared with `n` processes count as `1/n`.
+    pub pss: usize,
+    /// Resident, shared with other processes and not modified.
+    pub shared_clean: usize,
+    /// Resident, shared with other processes and modified.
+    pub shared_dirty: usize,
+    /// Resident, used only by the process and not modified.
+    pub private_clean: usize,
+    /// Resident, used only by the process and modified.
+    pub private_dirty: usize,
+    /// Not backed by a file.
+    pub anonymous: usize,
+    /// Swapped out.
+    pub swap: usize,
+}
+
+impl MemoryUsage {
+    /// Resident memory that wasn't modified.
+    #[inline]
+    pub fn clean(&self) -> usize {
+        self.shared_clean + self.private_clean
+    }
+
+    /// Resident memory that was modified.
+    #[inline]
+    pub fn dirty(&self) -> usize {
+        self.shared_dirty + self.private_dirty
+    }
+}
+
+impl Add for MemoryUsage {
+    type Output = Self;
+
+    fn add(mut self, rhs: Self) -> Self {
+        self += rhs;
+        self
+    }
+}
+
+impl AddAssign for MemoryUsage {
+    fn add_assign(&mut self, rhs: Self) {
+        self.rss += rhs.rss;
+        self.pss += rhs.pss;
+        self.shared_clean += rhs.shared_clean;
+        self.shared_dirty += rhs.shared_dirty;
+        self.private_clean += rhs.private_clean;
+        self.private_dirty += rhs.private_dirty;
+        self.anonymous += rhs.anonymous;
+        self.swap += rhs.swap;
+    }
+}
+
+impl Sum for MemoryUsage {
+    fn sum<I: Iterator<Item = Self>>(iter: I) -> Self {
+        iter.fold(Self::default(), Add::add)
+    }
+}
+
+/// Memory usage of a single mapped region.
+#[derive(Debug)]
+pub struct RegionStats {
+    /// The region.
+    pub region: MemoryRegion,
+    /// Mapped file or a pseudo path like `[heap]`, `None` for anonymous mappings.
+    pub path: Option<String>,
+    /// Memory usage of the region.
+    pub usage: MemoryUsage,
+}
+
+/// Memory usage of all regions of a module.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ModuleStats {
+    /// Module's name
+    pub name: String,
+    /// Path to the module in the process's mount namespace.
+    pub path: String,
+    /// Module's base
+    pub base: usize,
+    /// Module's size
+    pub size: usize,
+    /// Memory usage of the module's regions.
+    pub usage: MemoryUsage,
+}
+
+impl OwnedProcess {
+    /// Returns memory usage of every mapped region, parsed from `/proc/<pid>/smaps`.
+    /// ```no_run
+    /// # use memflex::external::{find_process_by_name, Scanner};
+    /// let p = find_process_by_name("game")?;
+    ///
+    /// // Skip regions that are mostly not resident.
+    /// let resident = p
+    ///     .region_stats()?
+    ///     .into_iter()
+    ///     .filter(|s| s.usage.rss * 2 >= s.region.to - s.region.from)
+    ///     .map(|s| s.region.from)
+    ///     .collect::<Vec<_>>();
+    /// let scanner = Scanner::<u32>::new(&p)
+    ///     .regions(move |r| r.prot.read() && r.prot.write() && resident.contains(&r.from));
+    /// # Ok::<_, memflex::MfError>(())
+    /// ```
+    pub fn region_stats(&self) -> crate::Result<Vec<RegionStats>> {
+        let smaps = String::from_utf8_lossy(&self.proc_file("smaps")?).into_owned();
+        let mut stats: Vec<RegionStats> = vec![];
+
+        for l in smaps.lines() {
+            let (key, value) = l.split_once(char::is_whitespace).unwrap_or((l, ""));
+
+            if let Some((from, to)) = key.split_once('-') {
+                let (Ok(from), Ok(to)) = (
+                    usize::from_str_radix(from, 16),
+                    usize::from_str_radix(to, 16),
+                ) else {
+                    continue;
+                };
+
+                let mut fields = value.splitn(5, ' ');
+                let prot =
+                    Protection::parse(fields.next().unwrap_or("---").get(0..3).unwrap_or("---"));
+                let path = fields.nth(3).map(str::trim_start).filter(|p| !p.is_empty());
+
+                stats.push(RegionStats {
+                    region: MemoryRegion { from, to, prot },
+                    path: path.map(str::to_owned),
+                    usage: MemoryUsage::default(),
+                });
+                continue;
+            }
+
+            let Some(usage) = stats.last_mut().map(|s| &mut s.usage) else {
+                continue;
+            };
+            let Some(kb) = value
+                .trim()
+                .strip_suffix(" kB")
+                .and_then(|v| v.parse::<usize>().ok())
+            else {
+                continue;
+            };
+
+            let field = match key {
+                "Rss:" => &mut usage.rss,
+                "Pss:" => &mut usage.pss,
+                "Shared_Clean:" => &mut usage.shared_clean,
+                "Shared_Dirty:" => &mut usage.shared_dirty,
+                "Private_Clean:" => &mut usage.private_clean,
+                "Private_Dirty:" => &mut usage.private_dirty,
+                "Anonymous:" => &mut usage.anonymous,
+                "Swap:" => &mut usage.swap,
+                _ => continue,
+            };
+            *field = kb * 1024;
+        }
+
+        Ok(stats)
+    }
+
+    /// Returns memory usage of every module, summed over its regions.
+    /// # Behavior
+    /// Like in [`OwnedProcess::modules`], anonymous `.bss` mapping that directly follows
+    /// the module is counted as its part.
+    pub fn module_stats(&self) -> crate::Result<Vec<ModuleStats>> {
+        let mut modules: Vec<ModuleStats> = vec![];
+        let mut index = HashMap::new();
+        // Module that the previous region belonged to.
+        let mut last: Option<usize> = None;
+
+        for s in self.region_stats()? {
+            let MemoryRegion { from, to, .. } = s.region;
+            let file = s.path.as_deref().is_some_and(|p| p.starts_with('/'));
+
+            let i = match (s.path, last.take()) {
+                (Some(path), _) if path.starts_with('/') => {
+                    *index.entry(path.clone()).or_insert_with(|| {
+                        modules.push(ModuleStats {
+                            name: Path::new(&path)
+                                .file_name()
+                                .map(|n| n.to_string_lossy().into_owned())
+                                .unwrap_or_default(),
+                            path,
+                            base: from,
+                            size: 0,
+                            usage: MemoryUsage::default(),
+                        });
+                        modules.len() - 1
+                    })
+                }
+                // Anonymous mapping right after the module is its `.bss`.
+                (None, Some(i)) if modules[i].base + modules[i].size == from => i,
+                _ => continue,
+            };
+
+            let m = &mut modules[i];
+            let end = (m.base + m.size).max(to);
+            m.base = m.base.min(from);
+            m.size = end - m.base;
+            m.usage += s.usage;
+            last = file.then_some(i);
+        }
+
+        Ok(modules)
+    }
+}
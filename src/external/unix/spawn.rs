@@ -0,0 +1,442 @@
+use super::{io_error, OwnedProcess};
+use crate::MfError;
+use core::{marker::PhantomData, mem::zeroed, ptr::null_mut};
+use std::{
+    collections::HashMap,
+    ffi::{CString, OsStr},
+    fs::{File, OpenOptions},
+    os::unix::{ffi::OsStrExt, fs::FileExt, process::CommandExt},
+    path::Path,
+    process::{Child, Command},
+};
+
+/// Reason the traced process stopped.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Stop {
+    /// Breakpoint at the address was hit, instruction pointer is set to the address.
+    Breakpoint(usize),
+    /// Process received a signal, it will be delivered on the next continue.
+    /// `SIGTRAP` that isn't a breakpoint is caused by tracing and is never delivered.
+    Signal(i32),
+}
+
+/// Handle to a process started with [`spawn_suspended`].
+/// # Behavior
+/// The process is traced with `ptrace` and stays stopped until [`SuspendedProcess::resume`]
+/// is called or the handle is dropped. Once dropped without [`SuspendedProcess::resume`],
+/// the process is reaped on a background thread after it exits.
+/// All ptrace requests must come from the thread that spawned the process, so
+/// the handle is neither [`Send`] nor [`Sync`].
+pub struct SuspendedProcess {
+    pid: u32,
+    /// Taken by [`SuspendedProcess::resume`], so the caller can reap the process.
+    child: Option<Child>,
+    mem: File,
+    breakpoints: HashMap<usize, u8>,
+    pending_signal: i32,
+    detached: bool,
+    _thread: PhantomData<*const ()>,
+}
+
+/// Starts the process at `path` stopped at its very first instruction, i.e. the entry
+/// of the dynamic loader, before any library is loaded or initializer is run.
+/// # Behavior
+/// * `args` don't include `argv[0]`, it's set to `path`.
+/// * `env` replaces the environment, pass [`std::env::vars_os`] to inherit it.
+/// * The process is killed if the calling process exits before resuming it.
+/// ```no_run
+/// # use memflex::external::spawn_suspended;
+/// let (game, mut handle) = spawn_suspended("./game", ["--windowed"], std::env::vars_os())?;
+/// handle.run_until(handle.entry_point()?)?;
+///
+/// let module = game.find_module("libengine.so")?;
+/// handle.write_buf(module.base as usize + 0x1234, &[0x90, 0x90])?;
+/// handle.resume()?.wait()?;
+/// # Ok::<_, memflex::MfError>(())
+/// ```
+pub fn spawn_suspended<A, K, V>(
+    path: impl AsRef<Path>,
+    args: impl IntoIterator<Item = A>,
+    env: impl IntoIterator<Item = (K, V)>,
+) -> crate::Result<(OwnedProcess, SuspendedProcess)>
+where
+    A: AsRef<OsStr>,
+    K: AsRef<OsStr>,
+    V: AsRef<OsStr>,
+{
+    let mut command = Command::new(path.as_ref());
+    command.args(args).env_clear().envs(env);
+
+    unsafe {
+        command.pre_exec(|| {
+            if libc::ptrace(libc::PTRACE_TRACEME, 0, null_mut::<()>(), null_mut::<()>()) == -1 {
+                Err(std::io::Error::last_os_error())
+            } else {
+                Ok(())
+            }
+        });
+    }
+
+    let mut child = command.spawn().map_err(io_error)?;
+    let pid = child.id();
+
+    let mem = trace_options(pid).and_then(|_| {
+        OpenOptions::new()
+            .read(true)
+            .write(true)
+            .open(format!("/proc/{pid}/mem"))
+            .map_err(io_error)
+    });
+    let mem = match mem {
+        Ok(mem) => mem,
+        Err(e) => {
+            _ = child.kill();
+            _ = child.wait();
+            return Err(e);
+        }
+    };
+
+    let handle = SuspendedProcess {
+        pid,
+        child: Some(child),
+        mem,
+        breakpoints: HashMap::new(),
+        pending_signal: 0,
+        detached: false,
+        _thread: PhantomData,
+    };
+
+    Ok((OwnedProcess::open(pid)?, handle))
+}
+
+/// Waits for the initial stop of the process and makes it die with the tracer.
+fn trace_options(pid: u32) -> crate::Result<()> {
+    // Stops with SIGTRAP right after execve.
+    if wait_stop(pid)? != libc::SIGTRAP {
+        return Err(MfError::ProcessDied);
+    }
+
+    unsafe {
+        let options = libc::PTRACE_O_EXITKILL as usize as *mut ();
+        if libc::ptrace(
+            libc::PTRACE_SETOPTIONS,
+            pid as libc::pid_t,
+            null_mut::<()>(),
+            options,
+        ) == -1
+        {
+            return MfError::last();
+        }
+    }
+
+    Ok(())
+}
+
+/// Waits for the process to stop, returning the signal it was stopped by.
+fn wait_stop(pid: u32) -> crate::Result<i32> {
+    let mut status = 0;
+    loop {
+        if unsafe { libc::waitpid(pid as _, &mut status, libc::__WALL) } == -1 {
+            match MfError::last::<()>() {
+                Err(MfError::Errno(libc::EINTR)) => continue,
+                Err(e) => return Err(e),
+                Ok(_) => unreachable!(),
+            }
+        }
+
+        if libc::WIFSTOPPED(status) {
+            return Ok(libc::WSTOPSIG(status));
+        } else if libc::WIFEXITED(status) || libc::WIFSIGNALED(status) {
+            return Err(MfError::ProcessDied);
+        }
+    }
+}
+
+impl SuspendedProcess {
+    fn ptrace(&self, request: libc::c_uint, data: usize) -> crate::Result<()> {
+        unsafe {
+            if libc::ptrace(request, self.pid as libc::pid_t, null_mut::<()>(), data) == -1 {
+                MfError::last()
+            } else {
+                Ok(())
+            }
+        }
+    }
+
+    /// Returns the id of the process.
+    #[inline]
+    pub fn id(&self) -> u32 {
+        self.pid
+    }
+
+    /// Reads process memory, returning amount of bytes read.
+    pub fn read_buf(&self, address: usize, buf: &mut [u8]) -> crate::Result<usize> {
+        self.mem.read_at(buf, address as u64).map_err(io_error)
+    }
+
+    /// Writes process memory, returning amount of bytes written.
+    /// # Behavior
+    /// Memory protection is ignored, so code can be patched without changing it.
+    pub fn write_buf(&self, address: usize, buf: &[u8]) -> crate::Result<usize> {
+        self.mem.write_at(buf, address as u64).map_err(io_error)
+    }
+
+    /// Writes `value` at `address`, ignoring memory protection.
+    pub fn write<T>(&self, address: usize, value: &T) -> crate::Result<usize> {
+        unsafe {
+            self.write_buf(
+                address,
+                core::slice::from_raw_parts(value as *const T as *const u8, size_of::<T>()),
+            )
+        }
+    }
+
+    /// Returns the entry point of the executable from the auxiliary vector.
+    /// Running until it guarantees that all libraries the executable depends on are loaded.
+    pub fn entry_point(&self) -> crate::Result<usize> {
+        let auxv = std::fs::read(format!("/proc/{}/auxv", self.pid)).map_err(io_error)?;
+
+        auxv.chunks_exact(size_of::<usize>() * 2)
+            .map(|kv| {
+                let (k, v) = kv.split_at(size_of::<usize>());
+                (
+                    usize::from_ne_bytes(k.try_into().unwrap()),
+                    usize::from_ne_bytes(v.try_into().unwrap()),
+                )
+            })
+            .find(|(k, _)| *k == libc::AT_ENTRY as usize)
+            .map(|(_, v)| v)
+            .ok_or(MfError::ProcessDied)
+    }
+
+    /// Returns general purpose registers of the main thread.
+    pub fn regs(&self) -> crate::Result<libc::user_regs_struct> {
+        unsafe {
+            let mut regs: libc::user_regs_struct = zeroed();
+            self.ptrace(libc::PTRACE_GETREGS, &mut regs as *mut _ as usize)?;
+            Ok(regs)
+        }
+    }
+
+    /// Sets general purpose registers of the main thread.
+    pub fn set_regs(&self, regs: &libc::user_regs_struct) -> crate::Result<()> {
+        self.ptrace(libc::PTRACE_SETREGS, regs as *const _ as usize)
+    }
+
+    /// Places `int3` breakpoint at the address.
+    pub fn set_breakpoint(&mut self, address: usize) -> crate::Result<()> {
+        if self.breakpoints.contains_key(&address) {
+            return Ok(());
+        }
+
+        let mut orig = [0];
+        self.read_buf(address, &mut orig)?;
+        self.write_buf(address, &[0xCC])?;
+        self.breakpoints.insert(address, orig[0]);
+
+        Ok(())
+    }
+
+    /// Removes previously placed breakpoint, restoring the original byte.
+    pub fn remove_breakpoint(&mut self, address: usize) -> crate::Result<()> {
+        if let Some(orig) = self.breakpoints.remove(&address) {
+            self.write_buf(address, &[orig])?;
+        }
+
+        Ok(())
+    }
+
+    /// Executes a single instruction, stepping over a breakpoint if it's placed there.
+    fn step(&mut self) -> crate::Result<()> {
+        let rip = self.regs()?.rip as usize;
+        let orig = self.breakpoints.get(&rip).copied();
+
+        if let Some(orig) = orig {
+            self.write_buf(rip, &[orig])?;
+        }
+
+        self.ptrace(libc::PTRACE_SINGLESTEP, self.pending_signal as usize)?;
+        self.pending_signal = 0;
+        let sig = wait_stop(self.pid)?;
+
+        if orig.is_some() {
+            self.write_buf(rip, &[0xCC])?;
+        }
+
+        if sig != libc::SIGTRAP {
+            self.pending_signal = sig;
+        }
+
+        Ok(())
+    }
+
+    /// Continues the process until it hits a breakpoint or receives a signal.
+    /// # Errors
+    /// [`MfError::ProcessDied`] if the process exited.
+    pub fn cont(&mut self) -> crate::Result<Stop> {
+        self.step()?;
+        if self.pending_signal != 0 {
+            return Ok(Stop::Signal(self.pending_signal));
+        }
+
+        self.ptrace(libc::PTRACE_CONT, 0)?;
+        let sig = wait_stop(self.pid)?;
+
+        if sig == libc::SIGTRAP {
+            let mut regs = self.regs()?;
+            let address = (regs.rip as usize).wrapping_sub(1);
+
+            if self.breakpoints.contains_key(&address) {
+                regs.rip -= 1;
+                self.set_regs(&regs)?;
+
+                return Ok(Stop::Breakpoint(address));
+            }
+        } else {
+            self.pending_signal = sig;
+        }
+
+        Ok(Stop::Signal(sig))
+    }
+
+    /// Continues the process until it reaches the address.
+    /// # Behavior
+    /// Signals received in the meantime are delivered to the process.
+    pub fn run_until(&mut self, address: usize) -> crate::Result<()> {
+        let placed = !self.breakpoints.contains_key(&address);
+        self.set_breakpoint(address)?;
+
+        while self.cont()? != Stop::Breakpoint(address) {}
+
+        if placed {
+            self.remove_breakpoint(address)?;
+        }
+
+        Ok(())
+    }
+
+    /// Loads the library into the process by calling `dlopen` in it, returning the handle.
+    /// # Behavior
+    /// * Requires `libc.so.6` to be loaded in the process, so [`SuspendedProcess::run_until`]
+    ///   the [`SuspendedProcess::entry_point`] first. Assumes the process uses the same
+    ///   `libc.so.6` as the current process.
+    /// * Breakpoints hit during the call are stepped over.
+    /// * Signals received during the call are delivered on the next continue.
+    /// # Errors
+    /// * [`MfError::ModuleNotFound`] if libc is not loaded or `dlopen` failed.
+    /// * [`MfError::CallFaulted`] if the process faulted during the call, e.g. in a constructor
+    ///   of the library. Registers are restored, but the loader may be left locked.
+    pub fn inject_library(&mut self, path: impl AsRef<Path>) -> crate::Result<usize> {
+        let path = CString::new(path.as_ref().as_os_str().as_bytes())
+            .map_err(|_| MfError::InvalidString)?;
+
+        let dlopen = {
+            let ours = OwnedProcess::from_pid(std::process::id()).find_module("libc.so.6")?;
+            let theirs = OwnedProcess::from_pid(self.pid).find_module("libc.so.6")?;
+            let dlopen = unsafe { libc::dlsym(libc::RTLD_DEFAULT, c"dlopen".as_ptr()) } as usize;
+
+            theirs.base as usize + (dlopen - ours.base as usize)
+        };
+
+        let saved = self.regs()?;
+        let mut regs = saved;
+
+        // Skip the red zone and put the path on the stack.
+        let path = path.as_bytes_with_nul();
+        let string = (saved.rsp as usize - 0x100 - path.len()) & !0xF;
+        self.write_buf(string, path)?;
+
+        // Zero return address makes the process fault once `dlopen` returns.
+        let rsp = string - 0x8;
+        self.write(rsp, &0_usize)?;
+
+        regs.rsp = rsp as _;
+        regs.rip = dlopen as _;
+        regs.rdi = string as _;
+        regs.rsi = (libc::RTLD_NOW | libc::RTLD_GLOBAL) as _;
+        regs.rax = 0;
+        // Prevents the kernel from restarting interrupted syscall.
+        regs.orig_rax = u64::MAX;
+        self.set_regs(&regs)?;
+
+        let signal = self.pending_signal;
+        self.pending_signal = 0;
+
+        // Signals that aren't faults are held back until the call finishes.
+        let mut received = vec![];
+        let result = loop {
+            self.ptrace(libc::PTRACE_CONT, 0)?;
+            let mut sig = wait_stop(self.pid)?;
+            let mut regs = self.regs()?;
+
+            let address = (regs.rip as usize).wrapping_sub(1);
+            if sig == libc::SIGTRAP && self.breakpoints.contains_key(&address) {
+                regs.rip -= 1;
+                self.set_regs(&regs)?;
+                self.step()?;
+
+                sig = core::mem::take(&mut self.pending_signal);
+                regs = self.regs()?;
+            }
+
+            match sig {
+                libc::SIGSEGV if regs.rip == 0 => break Ok(regs.rax as usize),
+                libc::SIGSEGV
+                | libc::SIGBUS
+                | libc::SIGILL
+                | libc::SIGFPE
+                | libc::SIGABRT
+                | libc::SIGSYS => break Err(MfError::CallFaulted(sig)),
+                // Stepped over a breakpoint or stopped by tracing.
+                0 | libc::SIGTRAP => {}
+                sig => received.push(sig),
+            }
+        };
+
+        self.set_regs(&saved)?;
+        self.pending_signal = signal;
+        for sig in received {
+            if self.pending_signal == 0 {
+                self.pending_signal = sig;
+            } else {
+                // Raised again, so it's reported on the next continue.
+                unsafe { libc::syscall(libc::SYS_tgkill, self.pid, self.pid, sig) };
+            }
+        }
+
+        match result? {
+            0 => Err(MfError::ModuleNotFound),
+            handle => Ok(handle),
+        }
+    }
+
+    fn detach(&mut self) -> crate::Result<()> {
+        self.detached = true;
+
+        let addresses = self.breakpoints.keys().copied().collect::<Vec<_>>();
+        for address in addresses {
+            self.remove_breakpoint(address)?;
+        }
+
+        self.ptrace(libc::PTRACE_DETACH, self.pending_signal as usize)
+    }
+
+    /// Removes all breakpoints and lets the process run freely.
+    /// Returns the child, it has to be waited for to not leave a zombie behind.
+    pub fn resume(mut self) -> crate::Result<Child> {
+        self.detach()?;
+        Ok(self.child.take().unwrap())
+    }
+}
+
+impl Drop for SuspendedProcess {
+    fn drop(&mut self) {
+        if !self.detached {
+            _ = self.detach();
+        }
+
+        if let Some(mut child) = self.child.take() {
+            std::thread::spawn(move || child.wait());
+        }
+    }
+}
@@ -0,0 +1,132 @@
+use super::OwnedProcess;
+use crate::{MemorySource, MfError};
+
+impl OwnedProcess {
+    /// Reads bytes of a zero terminated string at `address`, up to `max_len` bytes.
+    pub fn read_str_bytes(&self, address: usize, max_len: usize) -> crate::Result<Vec<u8>> {
+        MemorySource::read_str_bytes(self, address, max_len)
+    }
+
+    /// Reads zero terminated UTF-8 string at `address`.
+    /// # Behavior
+    /// Reads at most 4096 bytes, use [`OwnedProcess::read_str_bounded`] for longer strings.
+    /// # Errors
+    /// [`MfError::UnterminatedString`] if there is no terminator within the 4096 bytes.
+    pub fn read_str(&self, address: usize) -> crate::Result<String> {
+        MemorySource::read_str(self, address)
+    }
+
+    /// Reads zero terminated UTF-8 string at `address`, up to `max_len` bytes.
+    pub fn read_str_bounded(&self, address: usize, max_len: usize) -> crate::Result<String> {
+        MemorySource::read_str_bounded(self, address, max_len)
+    }
+
+    /// Reads zero terminated string at `address`, up to `max_len` bytes,
+    /// replacing invalid UTF-8 sequences.
+    pub fn read_str_lossy(&self, address: usize, max_len: usize) -> crate::Result<String> {
+        MemorySource::read_str_lossy(self, address, max_len)
+    }
+
+    /// Reads code units of a zero terminated UTF-16 string at `address`, up to `max_len` of them.
+    pub fn read_utf16_units(&self, address: usize, max_len: usize) -> crate::Result<Vec<u16>> {
+        Ok(MemorySource::read_terminated(self, address, 2, max_len)?
+            .chunks_exact(2)
+            .map(|c| u16::from_ne_bytes([c[0], c[1]]))
+            .collect())
+    }
+
+    /// Reads zero terminated UTF-16 string at `address`, up to `max_len` code units.
+    pub fn read_utf16(&self, address: usize, max_len: usize) -> crate::Result<String> {
+        String::from_utf16(&self.read_utf16_units(address, max_len)?)
+            .map_err(|_| MfError::InvalidString)
+    }
+
+    /// Reads zero terminated UTF-16 string at `address`, up to `max_len` code units,
+    /// replacing invalid sequences.
+    pub fn read_utf16_lossy(&self, address: usize, max_len: usize) -> crate::Result<String> {
+        Ok(String::from_utf16_lossy(
+            &self.read_utf16_units(address, max_len)?,
+        ))
+    }
+
+    /// Reads code units of a zero terminated `wchar_t` (UTF-32) string at `address`,
+    /// up to `max_len` of them.
+    pub fn read_wstr_units(&self, address: usize, max_len: usize) -> crate::Result<Vec<u32>> {
+        Ok(MemorySource::read_terminated(self, address, 4, max_len)?
+            .chunks_exact(4)
+            .map(|c| u32::from_ne_bytes([c[0], c[1], c[2], c[3]]))
+            .collect())
+    }
+
+    /// Reads zero terminated `wchar_t` (UTF-32) string at `address`, up to `max_len` characters.
+    pub fn read_wstr(&self, address: usize, max_len: usize) -> crate::Result<String> {
+        self.read_wstr_units(address, max_len)?
+            .into_iter()
+            .map(char::from_u32)
+            .collect::<Option<String>>()
+            .ok_or(MfError::InvalidString)
+    }
+
+    /// Reads zero terminated `wchar_t` (UTF-32) string at `address`, up to `max_len` characters,
+    /// replacing invalid characters.
+    pub fn read_wstr_lossy(&self, address: usize, max_len: usize) -> crate::Result<String> {
+        Ok(self
+            .read_wstr_units(address, max_len)?
+            .into_iter()
+            .map(|c| char::from_u32(c).unwrap_or(char::REPLACEMENT_CHARACTER))
+            .collect())
+    }
+
+    /// Writes UTF-8 string at `address`, followed by a zero byte if `terminate` is set.
+    /// Returns the amount of bytes written.
+    pub fn write_str(
+        &self,
+        address: usize,
+        text: impl AsRef<str>,
+        terminate: bool,
+    ) -> crate::Result<usize> {
+        let mut buf = text.as_ref().as_bytes().to_vec();
+        if terminate {
+            buf.push(0);
+        }
+
+        self.write_buf(address, &buf)
+    }
+
+    /// Writes UTF-16 string at `address`, followed by a zero code unit if `terminate` is set.
+    /// Returns the amount of bytes written.
+    pub fn write_utf16(
+        &self,
+        address: usize,
+        text: impl AsRef<str>,
+        terminate: bool,
+    ) -> crate::Result<usize> {
+        let buf = text
+            .as_ref()
+            .encode_utf16()
+            .chain(terminate.then_some(0))
+            .flat_map(u16::to_ne_bytes)
+            .collect::<Vec<_>>();
+
+        self.write_buf(address, &buf)
+    }
+
+    /// Writes `wchar_t` (UTF-32) string at `address`, followed by a zero character if
+    /// `terminate` is set. Returns the amount of bytes written.
+    pub fn write_wstr(
+        &self,
+        address: usize,
+        text: impl AsRef<str>,
+        terminate: bool,
+    ) -> crate::Result<usize> {
+        let buf = text
+            .as_ref()
+            .chars()
+            .map(u32::from)
+            .chain(terminate.then_some(0))
+            .flat_map(u32::to_ne_bytes)
+            .collect::<Vec<_>>();
+
+        self.write_buf(address, &buf)
+    }
+}
@@ -0,0 +1,249 @@
+use super::{Location, OwnedProcess, Ticker};
+use crate::MfError;
+use core::mem::size_of;
+use std::{
+    collections::HashMap,
+    sync::{
+        atomic::{AtomicU64, Ordering},
+        mpsc::{self, Receiver},
+        Arc, Mutex,
+    },
+    time::Duration,
+};
+
+/// Maximum gap between two watched values that are still read together.
+const MAX_GAP: usize = 0x100;
+/// Maximum amount of bytes read at once.
+const MAX_SPAN: usize = 0x1000;
+
+/// Shared with the background thread, so it's called without holding the entries lock.
+type Callback = Arc<Mutex<dyn FnMut(&[u8], &[u8]) + Send>>;
+
+struct Entry {
+    location: Location,
+    size: usize,
+    last: Option<Vec<u8>>,
+    callback: Callback,
+}
+
+/// Change of a value, passed to its callback once the entries are unlocked.
+struct Change {
+    callback: Callback,
+    old: Vec<u8>,
+    new: Vec<u8>,
+}
+
+#[derive(Default)]
+struct Shared {
+    entries: Mutex<HashMap<u64, Entry>>,
+    next_id: AtomicU64,
+}
+
+/// Polls values in the memory of a process on a background thread and reports their changes.
+/// ```no_run
+/// # use memflex::external::{find_process_by_name, Location, Watcher};
+/// # use std::time::Duration;
+/// let p = find_process_by_name("game")?;
+/// let watcher = Watcher::new(p, Duration::from_millis(50));
+///
+/// let _health = watcher.watch(0x5612_3456_7890, |old: f32, new: f32| {
+///     println!("Health: {old} -> {new}");
+/// });
+///
+/// let (_ammo, changes) = watcher.watch_channel::<u32>(Location::Multilevel {
+///     base: 0x5612_3456_0000,
+///     offsets: vec![0x1F_A0C8, 0x10],
+/// });
+/// for (old, new) in changes {
+///     println!("Ammo: {old} -> {new}");
+/// }
+/// # Ok::<_, memflex::MfError>(())
+/// ```
+pub struct Watcher {
+    process: Arc<OwnedProcess>,
+    shared: Arc<Shared>,
+    ticker: Ticker,
+}
+
+impl Watcher {
+    /// Starts the background thread that reads watched values every `interval`.
+    /// # Behavior
+    /// Values that are close to each other are read at once. Values that can't be read are
+    /// skipped until the next tick. The thread exits once the process dies.
+    pub fn new(process: impl Into<Arc<OwnedProcess>>, interval: Duration) -> Self {
+        let process = process.into();
+        let shared = Arc::new(Shared::default());
+
+        let ticker = Ticker::spawn(interval, {
+            let (process, shared) = (process.clone(), shared.clone());
+            move || tick(&process, &shared)
+        });
+
+        Self {
+            process,
+            shared,
+            ticker,
+        }
+    }
+
+    /// Process the values are read from.
+    #[inline]
+    pub fn process(&self) -> &Arc<OwnedProcess> {
+        &self.process
+    }
+
+    /// Checks if the background thread is still reading values.
+    pub fn is_running(&self) -> bool {
+        self.ticker.is_running()
+    }
+
+    /// Starts watching the value at the location, until the returned handle is dropped.
+    /// # Behavior
+    /// `callback` is called on the background thread with the old and the new value every time
+    /// the value changes. The first read value is only remembered.
+    /// Callbacks are called after the watched values are unlocked, so they may add or remove
+    /// watches, but a removed watch can still get the changes read before its removal.
+    pub fn watch<T: Copy + 'static>(
+        &self,
+        location: impl Into<Location>,
+        mut callback: impl FnMut(T, T) + Send + 'static,
+    ) -> WatchHandle {
+        self.insert(
+            location.into(),
+            size_of::<T>(),
+            Arc::new(Mutex::new(move |old: &[u8], new: &[u8]| {
+                callback(from_bytes(old), from_bytes(new))
+            })),
+        )
+    }
+
+    /// Starts watching the value at the location, sending the old and the new value every time
+    /// it changes.
+    pub fn watch_channel<T: Copy + Send + 'static>(
+        &self,
+        location: impl Into<Location>,
+    ) -> (WatchHandle, Receiver<(T, T)>) {
+        let (tx, rx) = mpsc::channel();
+        let handle = self.watch(location, move |old: T, new: T| _ = tx.send((old, new)));
+
+        (handle, rx)
+    }
+
+    fn insert(&self, location: Location, size: usize, callback: Callback) -> WatchHandle {
+        let id = self.shared.next_id.fetch_add(1, Ordering::Relaxed);
+        self.shared.entries.lock().unwrap().insert(
+            id,
+            Entry {
+                location,
+                size,
+                last: None,
+                callback,
+            },
+        );
+
+        WatchHandle {
+            shared: self.shared.clone(),
+            id,
+        }
+    }
+}
+
+/// Handle to a value watched by [`Watcher`]. Dropping it stops watching.
+pub struct WatchHandle {
+    shared: Arc<Shared>,
+    id: u64,
+}
+
+impl Drop for WatchHandle {
+    fn drop(&mut self) {
+        if let Ok(mut entries) = self.shared.entries.lock() {
+            entries.remove(&self.id);
+        }
+    }
+}
+
+/// Reads every watched value and calls callbacks of the changed ones.
+/// Returns `false` if the process has died.
+fn tick(process: &OwnedProcess, shared: &Shared) -> bool {
+    let changes = match read_changes(process, &mut shared.entries.lock().unwrap()) {
+        Some(changes) => changes,
+        None => return false,
+    };
+
+    for Change { callback, old, new } in changes {
+        if let Ok(mut callback) = callback.lock() {
+            (*callback)(&old, &new);
+        }
+    }
+
+    true
+}
+
+/// Reads every watched value, remembering and returning the changed ones.
+/// Returns `None` if the process has died.
+fn read_changes(process: &OwnedProcess, entries: &mut HashMap<u64, Entry>) -> Option<Vec<Change>> {
+    let mut resolved = vec![];
+    for (id, e) in entries.iter() {
+        match e.location.resolve(process) {
+            Ok(address) => resolved.push((address, e.size, *id)),
+            Err(MfError::ProcessDied) => return None,
+            Err(_) => {}
+        }
+    }
+    resolved.sort_unstable();
+
+    let mut changes = vec![];
+    let mut buf = vec![];
+    let mut i = 0;
+    while i < resolved.len() {
+        // Values from `i` to `j` are read at once.
+        let from = resolved[i].0;
+        let mut to = from + resolved[i].1;
+        let mut j = i + 1;
+        while let Some(&(address, size, _)) = resolved.get(j) {
+            if address > to + MAX_GAP || (address + size).max(to) - from > MAX_SPAN {
+                break;
+            }
+
+            to = to.max(address + size);
+            j += 1;
+        }
+
+        buf.resize(to - from, 0);
+        let read = match process.read_buf(from, &mut buf[..]) {
+            Ok(read) => read,
+            Err(MfError::ProcessDied) => return None,
+            Err(_) => 0,
+        };
+
+        for &(address, size, id) in &resolved[i..j] {
+            let offset = address - from;
+            if offset + size > read {
+                continue;
+            }
+
+            let e = entries.get_mut(&id).unwrap();
+            let new = &buf[offset..offset + size];
+            match &mut e.last {
+                Some(last) if last[..] != *new => {
+                    changes.push(Change {
+                        callback: e.callback.clone(),
+                        old: last.clone(),
+                        new: new.to_vec(),
+                    });
+                    last.copy_from_slice(new);
+                }
+                Some(_) => {}
+                None => e.last = Some(new.to_vec()),
+            }
+        }
+
+        i = j;
+    }
+
+    Some(changes)
+}
+
+fn from_bytes<T: Copy>(bytes: &[u8]) -> T {
+    unsafe { bytes.as_ptr().cast::<T>().read_unaligned() }
+}
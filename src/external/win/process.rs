@@ -1,10 +1,13 @@
 use super::{ModuleIterator, OwnedThread, ThreadIterator};
 use crate::{
-    external::{MemoryRegion, ProcessEntry},
+    external::{MemoryRegion, ProcessEntry, Wait},
     types::{ModuleInfoWithName, Protection},
-    Matcher, MfError,
+    Matcher, MemorySource, MfError,
+};
+use core::{
+    mem::{size_of, transmute, zeroed},
+    ops::Range,
 };
-use core::mem::{size_of, transmute, zeroed};
 use windows::Win32::{
     Foundation::{CloseHandle, BOOL, HANDLE, MAX_PATH},
     System::{
@@ -22,7 +25,8 @@ use windows::Win32::{
         },
         ProcessStatus::K32GetProcessImageFileNameW,
         Threading::{
-            CreateRemoteThread, GetProcessId, OpenProcess, TerminateProcess, PROCESS_ACCESS_RIGHTS,
+            CreateRemoteThread, GetProcessId, IsWow64Process, OpenProcess, TerminateProcess,
+            PROCESS_ACCESS_RIGHTS,
         },
     },
 };
@@ -123,48 +127,28 @@ impl OwnedProcess {
         }
     }
 
-    /// Reads process memory, returning the value read at the `address`.
-    pub fn read<T>(&self, address: usize) -> crate::Result<T> {
-        unsafe {
-            let mut buf: T = zeroed();
+    /// Reads process memory into the whole buffer, replacing bytes that can't be read with
+    /// `fill`. Returns address ranges that were read, see [`MemorySource::read_buf_partial`].
+    pub fn read_buf_partial(
+        &self,
+        address: usize,
+        buf: &mut [u8],
+        fill: u8,
+    ) -> crate::Result<Vec<Range<usize>>> {
+        MemorySource::read_buf_partial(self, address, buf, fill)
+    }
 
-            if ReadProcessMemory(
-                self.0,
-                address as _,
-                &mut buf as *mut T as _,
-                size_of::<T>() as _,
-                None,
-            )
-            .as_bool()
-            {
-                Ok(buf)
-            } else {
-                MfError::last()
-            }
-        }
+    /// Reads process memory, returning the value read at the `address`.
+    /// # Errors
+    /// [`MfError::InvalidAddress`] if only part of the value could be read.
+    pub fn read<T: Copy>(&self, address: usize) -> crate::Result<T> {
+        MemorySource::read(self, address)
     }
 
     /// Reads zero terminated string at `address`.
     pub fn read_str(&self, address: usize) -> crate::Result<String> {
-        const BUF_SIZE: usize = 4;
-
-        let mut out = vec![];
-        let mut offset = 0;
-
-        loop {
-            let buf = self.read::<[u8; BUF_SIZE]>(address + offset)?;
-
-            if let Some(i) = buf.iter().position(|b| *b == 0) {
-                out.extend_from_slice(&buf[..i]);
-                break;
-            } else {
-                out.extend_from_slice(&buf);
-            }
-
-            offset += BUF_SIZE
-        }
-
-        String::from_utf8(out).map_err(|_| MfError::InvalidString)
+        String::from_utf8(MemorySource::read_str_bytes(self, address, usize::MAX)?)
+            .map_err(|_| MfError::InvalidString)
     }
 
     /// Writes buffer to the process memory, returning the amount of bytes written.
@@ -325,38 +309,33 @@ impl OwnedProcess {
             .ok_or(MfError::ModuleNotFound)
     }
 
+    /// Waits until the module is loaded in the process.
+    /// # Errors
+    /// [`MfError::ModuleNotFound`] if the module was not loaded before the timeout.
+    pub fn wait_for_module(
+        &self,
+        module_name: &str,
+        wait: impl Into<Wait>,
+    ) -> crate::Result<ModuleInfoWithName> {
+        wait.into()
+            .poll(|| {
+                // Snapshot may fail while the process is still initializing.
+                Ok(self
+                    .modules()
+                    .ok()
+                    .and_then(|mut i| i.find(|me| me.name.eq_ignore_ascii_case(module_name))))
+            })?
+            .ok_or(MfError::ModuleNotFound)
+    }
+
     /// Finds all occurences of the pattern in a given range.
-    // @TODO: Can be optimized
     pub fn find_pattern<'a>(
         &'a self,
         pat: impl Matcher + 'a,
         start: usize,
         len: usize,
     ) -> impl Iterator<Item = usize> + 'a {
-        let mut offset = 0;
-        let mut buf = vec![0; pat.len()];
-
-        std::iter::from_fn(move || {
-            loop {
-                if self.read_buf(start + offset, &mut buf[..]).is_err() {
-                    return None;
-                }
-
-                if pat.matches(&buf[..]) {
-                    break;
-                }
-
-                offset += 1;
-
-                if offset >= len {
-                    return None;
-                }
-            }
-
-            offset += 1;
-            Some(start + offset - 1)
-        })
-        .fuse()
+        MemorySource::find_pattern(self, pat, start, len)
     }
 
     /// Finds all occurences of the pattern in the specified module.
@@ -369,17 +348,32 @@ impl OwnedProcess {
         Ok(self.find_pattern(pat, module.base as _, module.size))
     }
 
-    /// Resolves multilevel pointer
-    pub fn resolve_multilevel(&self, mut base: usize, offsets: &[usize]) -> crate::Result<usize> {
-        for (i, &o) in offsets.iter().enumerate() {
-            if i != offsets.len() - 1 {
-                base = self.read(base + o)?;
+    /// Returns the size of a pointer in the process, `4` for processes running under WOW64.
+    pub fn pointer_size(&self) -> crate::Result<usize> {
+        let mut wow64 = BOOL::default();
+        unsafe {
+            if IsWow64Process(self.0, &mut wow64).as_bool() {
+                Ok(if wow64.as_bool() {
+                    4
+                } else {
+                    size_of::<usize>()
+                })
             } else {
-                base += o;
+                MfError::last()
             }
         }
+    }
 
-        Ok(base)
+    /// Reads a pointer of the process's width, see [`OwnedProcess::pointer_size`].
+    pub fn read_pointer(&self, address: usize) -> crate::Result<usize> {
+        MemorySource::read_pointer(self, address)
+    }
+
+    /// Resolves multilevel pointer
+    /// # Behavior
+    /// Pointers are read with the process's width, see [`OwnedProcess::read_pointer`].
+    pub fn resolve_multilevel(&self, base: usize, offsets: &[usize]) -> crate::Result<usize> {
+        MemorySource::resolve_multilevel(self, base, offsets)
     }
 
     /// Terminates the process with the specified code.
@@ -427,6 +421,8 @@ impl OwnedProcess {
 }
 
 /// Iterator over all processes in the system.
+/// # Windows
+/// Never yields an error, the item is a [`crate::Result`] to match other platforms.
 pub struct ProcessIterator {
     h: HANDLE,
     entry: PROCESSENTRY32W,
@@ -458,7 +454,7 @@ impl ProcessIterator {
 }
 
 impl Iterator for ProcessIterator {
-    type Item = ProcessEntry;
+    type Item = crate::Result<ProcessEntry>;
 
     fn next(&mut self) -> Option<Self::Item> {
         if self.stop {
@@ -469,7 +465,7 @@ impl Iterator for ProcessIterator {
             let current = ProcessEntry::from(&self.entry);
             self.stop = !Process32NextW(self.h, &mut self.entry).as_bool();
 
-            return current;
+            current.map(Ok)
         }
     }
 }
@@ -489,6 +485,7 @@ pub fn open_process_by_name(
     access_rights: PROCESS_ACCESS_RIGHTS,
 ) -> crate::Result<OwnedProcess> {
     ProcessIterator::new()?
+        .flatten()
         .find_map(|pe| {
             if pe.name.contains(name) {
                 Some(pe.open(inherit_handle, access_rights))
@@ -499,6 +496,25 @@ pub fn open_process_by_name(
         .ok_or(MfError::ProcessNotFound)?
 }
 
+/// Waits until the process with the specified name is started and opens it.
+/// # Errors
+/// [`MfError::ProcessNotFound`] if the process was not found before the timeout.
+pub fn wait_for_process(
+    name: &str,
+    wait: impl Into<Wait>,
+    inherit_handle: bool,
+    access_rights: PROCESS_ACCESS_RIGHTS,
+) -> crate::Result<OwnedProcess> {
+    wait.into()
+        .poll(
+            || match open_process_by_name(name, inherit_handle, access_rights) {
+                Err(MfError::ProcessNotFound) => Ok(None),
+                r => r.map(Some),
+            },
+        )?
+        .ok_or(MfError::ProcessNotFound)
+}
+
 /// Tried to open process by id
 pub fn open_process_by_id(
     id: u32,
@@ -0,0 +1,270 @@
+use super::{allocate, free, protect, region, PAGE};
+use crate::{
+    types::Protection,
+    x86::{decode, Instruction, OpcodeMap, MAX_LEN},
+    MfError,
+};
+use core::{
+    marker::PhantomData,
+    mem::{size_of, transmute_copy},
+    ops::{Deref, Range},
+    ptr::addr_of,
+    slice::from_raw_parts,
+};
+
+/// Length of `jmp rel32` written over the target.
+const JMP_LEN: usize = 5;
+/// Size of memory holding the relay and the trampoline.
+const BLOCK: usize = 0x1000;
+/// Distance between addresses tried when allocating the block, allocation granularity on windows.
+const GRANULARITY: usize = 0x10000;
+/// Maximum distance between the target and the block, so both can reach each other with `rel32`.
+const REACH: usize = 0x7FF0_0000;
+
+/// Inline hook of an x86-64 function in the current process.
+///
+/// The start of the target is replaced with a jump to the detour. Overwritten instructions
+/// are moved to a trampoline that continues the target, it's called through `Deref` to get
+/// the original behavior.
+/// # Behavior
+/// * Target is restored on drop.
+/// * Protection of the target's pages is restored after they are patched.
+/// ```no_run
+/// # use memflex::internal::{find_symbol, InlineHook};
+/// # use std::sync::OnceLock;
+/// static HOOK: OnceLock<InlineHook<extern "C" fn(i32) -> i32>> = OnceLock::new();
+///
+/// extern "C" fn detour(fd: i32) -> i32 {
+///     println!("Closing {fd}");
+///     (HOOK.get().unwrap())(fd)
+/// }
+///
+/// let close = find_symbol("libc.so.6", "close").unwrap();
+/// _ = HOOK.set(unsafe { InlineHook::new(close, detour as _)? });
+/// # Ok::<_, memflex::MfError>(())
+/// ```
+pub struct InlineHook<F> {
+    /// Trampoline, read as `F` through `Deref`.
+    original: usize,
+    target: usize,
+    block: usize,
+    /// Overwritten bytes of the target.
+    prologue: Vec<u8>,
+    _ph: PhantomData<F>,
+}
+
+impl<F: Copy> InlineHook<F> {
+    /// Redirects calls of the function at `target` to `detour`.
+    /// # Errors
+    /// * [`MfError::InvalidInstruction`] if instructions at the target can't be moved to
+    ///   the trampoline, i.e. they can't be decoded or there is a branch into them.
+    /// * [`MfError::NoNearMemory`] if there is no free memory within 2GB of the target.
+    /// * [`MfError::InvalidAddress`] if the target isn't mapped.
+    /// # Panics
+    /// If `F` isn't pointer sized.
+    /// # Safety
+    /// * `target` must point to a function of type `F`.
+    /// * No thread may execute the first instructions of the target while the hook is
+    ///   installed or removed.
+    /// * The trampoline must not be running when the hook is dropped.
+    pub unsafe fn new(target: *const u8, detour: F) -> crate::Result<Self> {
+        assert_eq!(
+            size_of::<F>(),
+            size_of::<usize>(),
+            "Detour must be a function pointer"
+        );
+        let target = target as usize;
+        let detour = transmute_copy::<F, usize>(&detour);
+
+        // Instructions overwritten by the jump.
+        let mut instructions = vec![];
+        let mut len = 0;
+        while len < JMP_LEN {
+            // The instruction can be shorter than `MAX_LEN` at the end of the mapping.
+            let (mapped, _) = region(target + len)?;
+            let code = from_raw_parts(
+                (target + len) as *const u8,
+                MAX_LEN.min(mapped.end - target - len),
+            );
+            let ins = decode(code).ok_or(MfError::InvalidInstruction)?;
+            instructions.push((target + len, ins));
+            len += ins.len;
+        }
+
+        let block = allocate_near(target)?;
+        let mut code = vec![];
+        // Relay, the detour can be anywhere.
+        push_jmp_abs(&mut code, detour);
+
+        let original = block + code.len();
+        for (address, ins) in &instructions {
+            let bytes = from_raw_parts(*address as *const u8, ins.len);
+            let at = block + code.len();
+            match relocate(bytes, ins, *address, at, target..target + len) {
+                Ok(moved) => code.extend(moved),
+                Err(e) => {
+                    _ = free(block, BLOCK);
+                    return Err(e);
+                }
+            }
+        }
+        push_jmp_abs(&mut code, target + len);
+
+        let mut patch = vec![0xE9];
+        patch.extend(((block as isize - (target + JMP_LEN) as isize) as i32).to_le_bytes());
+        patch.resize(len, 0x90);
+
+        let result = write_code(block, &code).and_then(|_| {
+            let prologue = from_raw_parts(target as *const u8, len).to_vec();
+            write_code(target, &patch)?;
+            Ok(prologue)
+        });
+
+        match result {
+            Ok(prologue) => Ok(Self {
+                original,
+                target,
+                block,
+                prologue,
+                _ph: PhantomData,
+            }),
+            Err(e) => {
+                _ = free(block, BLOCK);
+                Err(e)
+            }
+        }
+    }
+}
+
+impl<F> InlineHook<F> {
+    /// Address of the hooked function.
+    #[inline]
+    pub fn target(&self) -> usize {
+        self.target
+    }
+
+    /// Address of the trampoline that runs the original function.
+    #[inline]
+    pub fn trampoline(&self) -> usize {
+        self.original
+    }
+}
+
+impl<F> Deref for InlineHook<F> {
+    type Target = F;
+
+    fn deref(&self) -> &Self::Target {
+        unsafe {
+            addr_of!(self.original)
+                .cast::<Self::Target>()
+                .as_ref()
+                .unwrap()
+        }
+    }
+}
+
+impl<F> Drop for InlineHook<F> {
+    fn drop(&mut self) {
+        unsafe {
+            if write_code(self.target, &self.prologue).is_ok() {
+                _ = free(self.block, BLOCK);
+            }
+        }
+    }
+}
+
+/// Allocates the block within [`REACH`] of the target, trying the closest addresses first.
+fn allocate_near(target: usize) -> crate::Result<usize> {
+    let base = target & !(GRANULARITY - 1);
+
+    for i in 1..REACH / GRANULARITY {
+        let below = base.checked_sub(i * GRANULARITY);
+        let above = base.checked_add(i * GRANULARITY);
+
+        for hint in [below, above].into_iter().flatten() {
+            // The hint is ignored if the memory is taken.
+            if let Ok(block) = allocate(Some(hint), BLOCK, Protection::RX) {
+                let block = block as usize;
+                if block.abs_diff(target) < REACH {
+                    return Ok(block);
+                }
+                _ = free(block, BLOCK);
+            }
+        }
+    }
+
+    Err(MfError::NoNearMemory)
+}
+
+/// Appends `jmp [rip]` followed by the destination.
+fn push_jmp_abs(code: &mut Vec<u8>, to: usize) {
+    code.extend([0xFF, 0x25, 0, 0, 0, 0]);
+    code.extend((to as u64).to_le_bytes());
+}
+
+/// Offset from `next` to `to` that fits into `rel32`.
+fn rel32(to: usize, next: usize) -> crate::Result<[u8; 4]> {
+    i32::try_from(to.wrapping_sub(next) as isize)
+        .map(i32::to_le_bytes)
+        .map_err(|_| MfError::InvalidInstruction)
+}
+
+/// Returns the instruction from `from` rewritten to run at `at`.
+fn relocate(
+    bytes: &[u8],
+    ins: &Instruction,
+    from: usize,
+    at: usize,
+    patched: Range<usize>,
+) -> crate::Result<Vec<u8>> {
+    let Some(to) = ins.target(from, bytes) else {
+        return Ok(bytes.to_vec());
+    };
+
+    match (ins.imm, ins.disp) {
+        (Some(imm), _) if ins.relative_branch => {
+            // Jumping to the start goes through the hook, anywhere else lands in the patch.
+            if to > patched.start && to < patched.end {
+                return Err(MfError::InvalidInstruction);
+            }
+
+            let mut moved = match (ins.map, ins.opcode, imm.size) {
+                (_, _, 4) => bytes[..imm.offset].to_vec(),
+                (OpcodeMap::Primary, 0xEB, 1) => vec![0xE9],
+                (OpcodeMap::Primary, jcc @ 0x70..=0x7F, 1) => vec![0x0F, jcc + 0x10],
+                // `loop` and `jrcxz` have no long form.
+                _ => return Err(MfError::InvalidInstruction),
+            };
+            let next = at + moved.len() + 4;
+            moved.extend(rel32(to, next)?);
+            Ok(moved)
+        }
+        (_, Some(disp)) => {
+            let mut moved = bytes.to_vec();
+            moved[disp.offset..][..4].copy_from_slice(&rel32(to, at + ins.len)?);
+            Ok(moved)
+        }
+        _ => Err(MfError::InvalidInstruction),
+    }
+}
+
+/// Copies the code to `address`, making its pages writable for the time of the copy.
+unsafe fn write_code(address: usize, code: &[u8]) -> crate::Result<()> {
+    let end = address + code.len();
+    // Code can span pages with different protections.
+    let mut pages = vec![];
+    let mut page = address & !(PAGE - 1);
+    while page < end {
+        pages.push((page, region(page)?.1));
+        page += PAGE;
+    }
+
+    for (page, prot) in &pages {
+        protect(*page, PAGE, *prot | Protection::W)?;
+    }
+    core::ptr::copy_nonoverlapping(code.as_ptr(), address as *mut u8, code.len());
+    for (page, prot) in pages {
+        protect(page, PAGE, prot)?;
+    }
+    Ok(())
+}
@@ -8,6 +8,18 @@ mod unix;
 #[cfg(unix)]
 pub use unix::*;
 
+#[cfg(all(target_arch = "x86_64", any(windows, target_os = "linux")))]
+mod hook;
+#[cfg(all(target_arch = "x86_64", any(windows, target_os = "linux")))]
+pub use hook::*;
+
+mod vmt;
+pub use vmt::*;
+
+/// Size of a memory page.
+#[cfg(any(windows, target_os = "linux"))]
+const PAGE: usize = 0x1000;
+
 /// Returns an information about current module
 /// # Behavior
 /// Looks up module by looking up RIP register.
@@ -0,0 +1,84 @@
+#[cfg(target_os = "linux")]
+mod module;
+#[cfg(target_os = "linux")]
+pub use module::*;
+#[cfg(target_os = "linux")]
+mod symbol;
+#[cfg(target_os = "linux")]
+pub use symbol::*;
+
+use crate::{types::Protection, MfError};
+
+/// Changes the protection of a memory region
+pub fn protect(address: usize, len: usize, prot: Protection) -> crate::Result<()> {
+    unsafe {
+        if libc::mprotect(address as _, len, prot.to_os()) == 0 {
+            Ok(())
+        } else {
+            MfError::last()
+        }
+    }
+}
+
+/// Returns the protection of memory at the address.
+/// # Errors
+/// [`MfError::InvalidAddress`] if the address isn't mapped.
+#[cfg(target_os = "linux")]
+pub fn protection(address: usize) -> crate::Result<Protection> {
+    region(address).map(|(_, prot)| prot)
+}
+
+/// Returns the mapping that contains the address and its protection.
+#[cfg(target_os = "linux")]
+pub(crate) fn region(address: usize) -> crate::Result<(core::ops::Range<usize>, Protection)> {
+    std::fs::read_to_string("/proc/self/maps")?
+        .lines()
+        .find_map(|l| {
+            let (range, rest) = l.split_once(' ')?;
+            let (from, to) = range.split_once('-')?;
+            let from = usize::from_str_radix(from, 16).ok()?;
+            let to = usize::from_str_radix(to, 16).ok()?;
+
+            if !(from..to).contains(&address) {
+                return None;
+            }
+            Some((from..to, Protection::parse(rest.get(0..3)?)))
+        })
+        .ok_or(MfError::InvalidAddress)
+}
+
+/// Allocates virtual memory
+pub fn allocate(address: Option<usize>, len: usize, prot: Protection) -> crate::Result<*mut u8> {
+    unsafe {
+        let addr = libc::mmap(
+            address.unwrap_or(0) as _,
+            len,
+            prot.to_os(),
+            libc::MAP_PRIVATE | libc::MAP_ANONYMOUS,
+            -1,
+            0,
+        );
+
+        if addr != libc::MAP_FAILED {
+            Ok(addr as _)
+        } else {
+            MfError::last()
+        }
+    }
+}
+
+/// Frees virtual memory
+pub fn free(address: usize, len: usize) -> crate::Result<()> {
+    unsafe {
+        if libc::munmap(address as _, len) == 0 {
+            Ok(())
+        } else {
+            MfError::last()
+        }
+    }
+}
+
+/// Returns the id of the current process
+pub fn pid() -> u32 {
+    unsafe { libc::getpid() as _ }
+}
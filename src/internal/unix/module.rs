@@ -0,0 +1,117 @@
+use crate::types::{ModuleInfo, ModuleInfoWithName};
+use core::{ffi::CStr, slice::from_raw_parts};
+use libc::{c_int, c_void, dl_phdr_info, size_t, PT_DYNAMIC, PT_LOAD};
+use std::path::PathBuf;
+
+/// Object loaded by the dynamic linker.
+pub(crate) struct LoadedObject {
+    /// Full path to the backing file.
+    pub path: PathBuf,
+    /// Difference between the virtual addresses in the file and in memory.
+    pub bias: usize,
+    /// Lowest address of the loaded segments.
+    pub base: usize,
+    /// Size of the memory spanned by the loaded segments.
+    pub size: usize,
+    /// Address of the `PT_DYNAMIC` segment.
+    pub dynamic: Option<usize>,
+}
+
+impl LoadedObject {
+    pub fn name(&self) -> String {
+        self.path
+            .file_name()
+            .map(|n| n.to_string_lossy().into_owned())
+            .unwrap_or_default()
+    }
+
+    pub fn contains(&self, address: usize) -> bool {
+        address >= self.base && address < self.base + self.size
+    }
+
+    pub fn to_module(&self) -> ModuleInfoWithName {
+        ModuleInfoWithName {
+            base: self.base as _,
+            size: self.size,
+            name: self.name(),
+        }
+    }
+}
+
+/// Collects all objects currently loaded in the process with `dl_iterate_phdr`.
+pub(crate) fn loaded_objects() -> Vec<LoadedObject> {
+    unsafe extern "C" fn callback(info: *mut dl_phdr_info, _: size_t, data: *mut c_void) -> c_int {
+        let info = &*info;
+        let objects = &mut *data.cast::<Vec<LoadedObject>>();
+
+        let bias = info.dlpi_addr as usize;
+        let phdrs = from_raw_parts(info.dlpi_phdr, info.dlpi_phnum as usize);
+
+        let (mut from, mut to) = (usize::MAX, 0);
+        let mut dynamic = None;
+        for ph in phdrs {
+            match ph.p_type {
+                PT_LOAD => {
+                    from = from.min(bias + ph.p_vaddr as usize);
+                    to = to.max(bias + (ph.p_vaddr + ph.p_memsz) as usize);
+                }
+                PT_DYNAMIC => dynamic = Some(bias + ph.p_vaddr as usize),
+                _ => {}
+            }
+        }
+
+        if from >= to {
+            return 0;
+        }
+
+        let name = if info.dlpi_name.is_null() {
+            ""
+        } else {
+            CStr::from_ptr(info.dlpi_name).to_str().unwrap_or_default()
+        };
+
+        // Main executable is reported with an empty name.
+        let path = if name.is_empty() && objects.is_empty() {
+            std::env::current_exe().unwrap_or_default()
+        } else {
+            PathBuf::from(name)
+        };
+
+        objects.push(LoadedObject {
+            path,
+            bias,
+            base: from,
+            size: to - from,
+            dynamic,
+        });
+
+        0
+    }
+
+    let mut objects: Vec<LoadedObject> = vec![];
+    unsafe {
+        libc::dl_iterate_phdr(Some(callback), &mut objects as *mut _ as _);
+    }
+
+    objects
+}
+
+/// Returns an iterator over all modules in the current process.
+/// # Behavior
+/// Modules are enumerated with `dl_iterate_phdr`, the main executable always comes first.
+pub fn modules() -> impl Iterator<Item = ModuleInfoWithName> {
+    loaded_objects().into_iter().map(|o| o.to_module())
+}
+
+/// Searches for a module by its name.
+/// # Behavior
+/// Compares the file name of every loaded object (ascii case insensetive).
+pub fn find_module_by_name(module_name: &str) -> Option<ModuleInfo> {
+    loaded_objects()
+        .into_iter()
+        .find(|o| o.name().eq_ignore_ascii_case(module_name))
+        .map(|o| ModuleInfo {
+            base: o.base as _,
+            size: o.size,
+        })
+}
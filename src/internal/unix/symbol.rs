@@ -0,0 +1,216 @@
+use super::{loaded_objects, LoadedObject};
+use crate::types::ModuleInfoWithName;
+use core::{ffi::CStr, mem::zeroed};
+use std::ffi::CString;
+
+#[cfg(target_pointer_width = "32")]
+use libc::Elf32_Sym as ElfSym;
+#[cfg(target_pointer_width = "64")]
+use libc::Elf64_Sym as ElfSym;
+
+const DT_NULL: isize = 0;
+const DT_HASH: isize = 4;
+const DT_STRTAB: isize = 5;
+const DT_SYMTAB: isize = 6;
+const DT_GNU_HASH: isize = 0x6ffffef5;
+
+#[repr(C)]
+struct ElfDyn {
+    tag: isize,
+    val: usize,
+}
+
+/// Information about the symbol nearest to an address.
+#[derive(Debug, Clone)]
+pub struct SymbolInfo {
+    /// Module that contains the address.
+    pub module: ModuleInfoWithName,
+    /// Name of the nearest preceding symbol, `None` if there is none.
+    pub name: Option<String>,
+    /// Address of the symbol, or of the module if the symbol is unknown.
+    pub address: *const u8,
+    /// Offset of the address from [`SymbolInfo::address`].
+    pub offset: usize,
+}
+
+/// Dynamic symbol table of a loaded object.
+struct DynSymbols {
+    symtab: *const ElfSym,
+    strtab: *const u8,
+    count: usize,
+    bias: usize,
+}
+
+impl DynSymbols {
+    /// Locates `.dynsym` through the `PT_DYNAMIC` segment of the object.
+    unsafe fn new(object: &LoadedObject) -> Option<Self> {
+        // Dynamic linkers usually relocate these entries in place, but not always.
+        let fix = |ptr: usize| {
+            if ptr < object.base {
+                ptr + object.bias
+            } else {
+                ptr
+            }
+        };
+
+        let mut dynamic = object.dynamic? as *const ElfDyn;
+        let (mut symtab, mut strtab, mut hash, mut gnu_hash) = (0, 0, 0, 0);
+
+        while (*dynamic).tag != DT_NULL {
+            let ElfDyn { tag, val } = *dynamic;
+            match tag {
+                DT_SYMTAB => symtab = fix(val),
+                DT_STRTAB => strtab = fix(val),
+                DT_HASH => hash = fix(val),
+                DT_GNU_HASH => gnu_hash = fix(val),
+                _ => {}
+            }
+            dynamic = dynamic.add(1);
+        }
+
+        if symtab == 0 || strtab == 0 {
+            return None;
+        }
+
+        let count = if hash != 0 {
+            // nchain equals to the amount of symbols.
+            (hash as *const u32).add(1).read() as usize
+        } else if gnu_hash != 0 {
+            gnu_hash_count(gnu_hash as *const u32)
+        } else {
+            return None;
+        };
+
+        Some(Self {
+            symtab: symtab as _,
+            strtab: strtab as _,
+            count,
+            bias: object.bias,
+        })
+    }
+
+    /// Iterates over defined symbols, yielding their name and address.
+    unsafe fn iter(&self) -> impl Iterator<Item = (&CStr, usize)> + '_ {
+        (0..self.count).filter_map(move |i| {
+            let sym = &*self.symtab.add(i);
+            if sym.st_shndx == 0 || sym.st_value == 0 {
+                return None;
+            }
+
+            let name = CStr::from_ptr(self.strtab.add(sym.st_name as usize).cast());
+            Some((name, self.bias + sym.st_value as usize))
+        })
+    }
+}
+
+/// Counts symbols in the table described by `DT_GNU_HASH` section.
+unsafe fn gnu_hash_count(table: *const u32) -> usize {
+    let nbuckets = table.read() as usize;
+    let symoffset = table.add(1).read() as usize;
+    let bloom_size = table.add(2).read() as usize;
+
+    let buckets = table.add(4).cast::<usize>().add(bloom_size).cast::<u32>();
+    let chains = buckets.add(nbuckets);
+
+    let last = (0..nbuckets)
+        .map(|i| buckets.add(i).read() as usize)
+        .max()
+        .unwrap_or(0);
+    if last < symoffset {
+        return symoffset;
+    }
+
+    let mut i = last;
+    // Lowest bit marks the end of a chain.
+    while chains.add(i - symoffset).read() & 1 == 0 {
+        i += 1;
+    }
+
+    i + 1
+}
+
+/// Resolves symbol through the dynamic linker, making sure it belongs to the `object`.
+unsafe fn dlsym_in(object: &LoadedObject, name: &CStr) -> Option<*const u8> {
+    let path = CString::new(object.path.to_string_lossy().into_owned()).ok()?;
+    let handle = libc::dlopen(path.as_ptr(), libc::RTLD_LAZY | libc::RTLD_NOLOAD);
+    if handle.is_null() {
+        return None;
+    }
+
+    let address = libc::dlsym(handle, name.as_ptr()) as usize;
+    libc::dlclose(handle);
+
+    if object.contains(address) {
+        Some(address as _)
+    } else {
+        None
+    }
+}
+
+/// Searches for an exported or dynamic symbol in the specified module.
+/// # Behavior
+/// Symbol is looked up with `dlsym` first. If it can't see the symbol, e.g. because
+/// it has non-default version, module's `.dynsym` table is parsed from memory.
+/// ```
+/// # #[cfg(target_os = "linux")] {
+/// let getpid = memflex::internal::find_symbol("libc.so.6", "getpid").unwrap();
+/// let info = memflex::internal::symbolize(getpid as usize + 1).unwrap();
+/// assert_eq!(info.module.name, "libc.so.6");
+/// assert_eq!(info.address, getpid);
+/// assert_eq!(info.offset, 1);
+/// # }
+/// ```
+pub fn find_symbol(module_name: &str, name: &str) -> Option<*const u8> {
+    let object = loaded_objects()
+        .into_iter()
+        .find(|o| o.name().eq_ignore_ascii_case(module_name))?;
+    let cname = CString::new(name).ok()?;
+
+    unsafe {
+        if let Some(address) = dlsym_in(&object, &cname) {
+            return Some(address);
+        }
+
+        DynSymbols::new(&object)?
+            .iter()
+            .find(|(sym, _)| *sym == cname.as_c_str())
+            .map(|(_, address)| address as _)
+    }
+}
+
+/// Finds the module and the nearest symbol for the code address.
+/// # Behavior
+/// Uses `dladdr` if possible, falling back to the module's `.dynsym` table.
+/// If no symbol precedes the address, offset is calculated from the module's base.
+pub fn symbolize(address: usize) -> Option<SymbolInfo> {
+    let object = loaded_objects().into_iter().find(|o| o.contains(address))?;
+
+    let (name, start) = unsafe {
+        let mut info: libc::Dl_info = zeroed();
+        if libc::dladdr(address as _, &mut info) != 0
+            && !info.dli_sname.is_null()
+            && object.contains(info.dli_saddr as usize)
+        {
+            let name = CStr::from_ptr(info.dli_sname)
+                .to_string_lossy()
+                .into_owned();
+            (Some(name), info.dli_saddr as usize)
+        } else {
+            DynSymbols::new(&object)
+                .and_then(|syms| {
+                    syms.iter()
+                        .filter(|(_, start)| *start <= address && object.contains(*start))
+                        .max_by_key(|(_, start)| *start)
+                        .map(|(name, start)| (Some(name.to_string_lossy().into_owned()), start))
+                })
+                .unwrap_or((None, object.base))
+        }
+    };
+
+    Some(SymbolInfo {
+        module: object.to_module(),
+        name,
+        address: start as _,
+        offset: address - start,
+    })
+}
@@ -0,0 +1,243 @@
+use crate::types::VmtPtr;
+use core::{
+    mem::{size_of, transmute_copy},
+    slice::from_raw_parts,
+};
+
+/// Entries before the first function that are copied with the table, they hold RTTI.
+const PREFIX: usize = 2;
+
+fn to_address<F: Copy>(f: F) -> usize {
+    assert_eq!(
+        size_of::<F>(),
+        size_of::<usize>(),
+        "F must be a function pointer"
+    );
+    unsafe { transmute_copy(&f) }
+}
+
+fn from_address<F: Copy>(address: usize) -> F {
+    assert_eq!(
+        size_of::<F>(),
+        size_of::<usize>(),
+        "F must be a function pointer"
+    );
+    unsafe { transmute_copy(&address) }
+}
+
+/// Copy of an object's virtual method table with some of the functions replaced.
+///
+/// Only the object's table pointer is changed, other objects of the same class keep
+/// using the original table.
+/// # Behavior
+/// * Two entries before the table are copied along with it, so RTTI keeps working.
+/// * On drop the object gets its original table back, unless its table pointer was
+///   changed by someone else.
+/// ```no_run
+/// # use memflex::{internal::ShadowVmt, types::VmtPtr};
+/// # let object: *mut VmtPtr = core::ptr::null_mut();
+/// extern "C" fn get_health(_: *const ()) -> i32 {
+///     100
+/// }
+///
+/// let mut shadow = unsafe { ShadowVmt::new(object, 10) };
+/// let original: extern "C" fn(*const ()) -> i32 = unsafe { shadow.hook(3, get_health) };
+/// ```
+pub struct ShadowVmt {
+    object: *mut VmtPtr,
+    original: *const usize,
+    /// Copy of the table with [`PREFIX`] entries before it.
+    table: Box<[usize]>,
+}
+
+impl ShadowVmt {
+    /// Copies `count` functions of the table and makes the object use the copy.
+    /// # Safety
+    /// * `object` must point to the table pointer of an object that outlives the hook.
+    /// * The table must have at least `count` functions and two entries before it.
+    pub unsafe fn new(object: *mut VmtPtr, count: usize) -> Self {
+        let original = (*object).as_ptr();
+        let table: Box<[usize]> = from_raw_parts(original.sub(PREFIX), count + PREFIX).into();
+        *object = VmtPtr::new(table.as_ptr().add(PREFIX));
+
+        Self {
+            object,
+            original,
+            table,
+        }
+    }
+
+    /// Copies functions of the table until the first `0`, see [`ShadowVmt::new`].
+    /// # Safety
+    /// * `object` must point to the table pointer of an object that outlives the hook.
+    /// * The table must be zero terminated and have two entries before it.
+    pub unsafe fn new_terminated(object: *mut VmtPtr) -> Self {
+        let count = (*object).dump_terminated().len();
+        Self::new(object, count)
+    }
+
+    /// Replaces the function at `index` in the copy, returning the original one.
+    /// # Panics
+    /// * If `index` is out of the copied functions.
+    /// * If `F` isn't pointer sized.
+    /// # Safety
+    /// `F` must be the type of the function at `index`.
+    pub unsafe fn hook<F: Copy>(&mut self, index: usize, detour: F) -> F {
+        self.table[PREFIX + index] = to_address(detour);
+        self.original(index)
+    }
+
+    /// Puts the original function back at `index`.
+    /// # Panics
+    /// If `index` is out of the copied functions.
+    pub fn unhook(&mut self, index: usize) {
+        self.table[PREFIX + index] = self.table_original(index);
+    }
+
+    /// Returns the original function at `index`.
+    /// # Panics
+    /// * If `index` is out of the copied functions.
+    /// * If `F` isn't pointer sized.
+    /// # Safety
+    /// `F` must be the type of the function at `index`.
+    pub unsafe fn original<F: Copy>(&self, index: usize) -> F {
+        from_address(self.table_original(index))
+    }
+
+    /// Original table the object used.
+    #[inline]
+    pub fn original_table(&self) -> VmtPtr {
+        VmtPtr::new(self.original)
+    }
+
+    fn table_original(&self, index: usize) -> usize {
+        assert!(PREFIX + index < self.table.len(), "Index out of the table");
+        unsafe { self.original.add(index).read() }
+    }
+}
+
+impl Drop for ShadowVmt {
+    fn drop(&mut self) {
+        unsafe {
+            if (*self.object).as_ptr() == self.table.as_ptr().add(PREFIX) {
+                *self.object = VmtPtr::new(self.original);
+            }
+        }
+    }
+}
+
+/// Hook that replaces functions in the virtual method table itself, so every object of
+/// the class is affected.
+/// # Behavior
+/// Pages of the table are made writable only for the time of the write, their protection
+/// is restored afterwards. Every hooked function is restored on drop.
+/// ```no_run
+/// # use memflex::{internal::VmtHook, types::VmtPtr};
+/// # let vmt = VmtPtr::new(core::ptr::null());
+/// extern "C" fn get_health(_: *const ()) -> i32 {
+///     100
+/// }
+///
+/// let mut hook = unsafe { VmtHook::new(&vmt) };
+/// let original: extern "C" fn(*const ()) -> i32 = unsafe { hook.hook(3, get_health)? };
+/// # Ok::<_, memflex::MfError>(())
+/// ```
+#[cfg(any(windows, target_os = "linux"))]
+pub struct VmtHook {
+    table: *mut usize,
+    /// Index and original function of every hooked entry.
+    hooked: Vec<(usize, usize)>,
+}
+
+#[cfg(any(windows, target_os = "linux"))]
+impl VmtHook {
+    /// Creates a hook of the table, nothing is changed until [`VmtHook::hook`] is called.
+    /// # Safety
+    /// The table must outlive the hook.
+    pub unsafe fn new(vmt: &VmtPtr) -> Self {
+        Self {
+            table: vmt.as_ptr() as _,
+            hooked: vec![],
+        }
+    }
+
+    /// Replaces the function at `index`, returning the original one. If the index is
+    /// already hooked, the detour is replaced and the function from before the first hook
+    /// is returned.
+    /// # Errors
+    /// If protection of the table can't be queried or changed.
+    /// # Panics
+    /// If `F` isn't pointer sized.
+    /// # Safety
+    /// * The table must have a function at `index`.
+    /// * `F` must be the type of the function at `index`.
+    pub unsafe fn hook<F: Copy>(&mut self, index: usize, detour: F) -> crate::Result<F> {
+        let entry = self.table.add(index);
+        let hooked = self.hooked.iter().position(|(i, _)| *i == index);
+        let original = match hooked {
+            Some(pos) => self.hooked[pos].1,
+            None => entry.read(),
+        };
+
+        write_entry(entry, to_address(detour))?;
+        if hooked.is_none() {
+            self.hooked.push((index, original));
+        }
+
+        Ok(from_address(original))
+    }
+
+    /// Puts the original function back at `index`, does nothing if it isn't hooked.
+    /// # Errors
+    /// If protection of the table can't be queried or changed.
+    pub fn unhook(&mut self, index: usize) -> crate::Result<()> {
+        let Some(pos) = self.hooked.iter().position(|(i, _)| *i == index) else {
+            return Ok(());
+        };
+
+        unsafe { write_entry(self.table.add(index), self.hooked[pos].1)? };
+        self.hooked.remove(pos);
+        Ok(())
+    }
+
+    /// Returns the original function at `index`, `None` if it isn't hooked.
+    /// # Panics
+    /// If `F` isn't pointer sized.
+    /// # Safety
+    /// `F` must be the type of the function at `index`.
+    pub unsafe fn original<F: Copy>(&self, index: usize) -> Option<F> {
+        self.hooked
+            .iter()
+            .find(|(i, _)| *i == index)
+            .map(|(_, original)| from_address(*original))
+    }
+}
+
+#[cfg(any(windows, target_os = "linux"))]
+impl Drop for VmtHook {
+    fn drop(&mut self) {
+        for (index, original) in self.hooked.drain(..) {
+            unsafe {
+                _ = write_entry(self.table.add(index), original);
+            }
+        }
+    }
+}
+
+/// Writes the entry, making its page writable if needed.
+#[cfg(any(windows, target_os = "linux"))]
+unsafe fn write_entry(entry: *mut usize, value: usize) -> crate::Result<()> {
+    use super::{protect, protection, PAGE};
+    use crate::types::Protection;
+
+    let prot = protection(entry as usize)?;
+    if prot.write() {
+        entry.write(value);
+        return Ok(());
+    }
+
+    let page = entry as usize & !(PAGE - 1);
+    protect(page, PAGE, prot | Protection::W)?;
+    entry.write(value);
+    protect(page, PAGE, prot)
+}
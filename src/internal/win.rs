@@ -1,8 +1,13 @@
+use crate::{types::Protection, MfError};
 use windows::Win32::{
     Foundation::HINSTANCE,
     System::{
         Console::{AllocConsole, FreeConsole},
         LibraryLoader::FreeLibraryAndExitThread,
+        Memory::{
+            VirtualAlloc, VirtualFree, VirtualProtect, VirtualQuery, MEMORY_BASIC_INFORMATION,
+            MEM_COMMIT, MEM_RELEASE, MEM_RESERVE, PAGE_PROTECTION_FLAGS,
+        },
     },
 };
 
@@ -55,6 +60,80 @@ pub fn modules() -> impl Iterator<Item = crate::types::ModuleInfoWithName> {
     }
 }
 
+/// Changes the protection of a memory region
+pub fn protect(address: usize, len: usize, prot: Protection) -> crate::Result<()> {
+    let mut old = PAGE_PROTECTION_FLAGS(0);
+    unsafe {
+        if VirtualProtect(address as _, len, prot.to_os(), &mut old).as_bool() {
+            Ok(())
+        } else {
+            MfError::last()
+        }
+    }
+}
+
+/// Returns the protection of memory at the address.
+/// # Errors
+/// [`MfError::InvalidAddress`] if the address isn't committed or has protection that
+/// can't be represented with [`Protection`].
+pub fn protection(address: usize) -> crate::Result<Protection> {
+    region(address).map(|(_, prot)| prot)
+}
+
+/// Returns the region of pages with the same protection that contains the address.
+pub(crate) fn region(address: usize) -> crate::Result<(core::ops::Range<usize>, Protection)> {
+    let mut info = MEMORY_BASIC_INFORMATION::default();
+    unsafe {
+        if VirtualQuery(
+            Some(address as _),
+            &mut info,
+            core::mem::size_of::<MEMORY_BASIC_INFORMATION>(),
+        ) == 0
+        {
+            return MfError::last();
+        }
+    }
+
+    if info.State != MEM_COMMIT {
+        return Err(MfError::InvalidAddress);
+    }
+    let prot = Protection::from_os(info.Protect).ok_or(MfError::InvalidAddress)?;
+    let base = info.BaseAddress as usize;
+    Ok((base..base + info.RegionSize, prot))
+}
+
+/// Allocates virtual memory
+pub fn allocate(address: Option<usize>, len: usize, prot: Protection) -> crate::Result<*mut u8> {
+    unsafe {
+        let addr = VirtualAlloc(
+            Some(address.unwrap_or(0) as _),
+            len,
+            MEM_COMMIT | MEM_RESERVE,
+            prot.to_os(),
+        );
+
+        if !addr.is_null() {
+            Ok(addr as _)
+        } else {
+            MfError::last()
+        }
+    }
+}
+
+/// Frees virtual memory
+/// # Behavior
+/// The whole allocation made by [`allocate`] is freed, `len` is ignored.
+pub fn free(address: usize, len: usize) -> crate::Result<()> {
+    _ = len;
+    unsafe {
+        if VirtualFree(address as _, 0, MEM_RELEASE).as_bool() {
+            Ok(())
+        } else {
+            MfError::last()
+        }
+    }
+}
+
 /// Allocates new console.
 pub fn alloc_console() -> bool {
     unsafe { AllocConsole().as_bool() }
@@ -20,6 +20,27 @@ pub use types::{TStr, VmtPtr};
 mod memory;
 pub use memory::*;
 
+mod source;
+pub use source::*;
+
+/// x86-64 instruction length decoder
+pub mod x86;
+
+#[cfg(feature = "std")]
+mod snapshot;
+#[cfg(feature = "std")]
+pub use snapshot::*;
+
+#[cfg(feature = "std")]
+mod coredump;
+#[cfg(feature = "std")]
+pub use coredump::*;
+
+#[cfg(feature = "std")]
+mod minidump;
+#[cfg(feature = "std")]
+pub use minidump::*;
+
 /// Some handy external API for interacting with the system
 #[cfg(feature = "external")]
 pub mod external;
@@ -73,8 +73,9 @@ macro_rules! __gen_func {
             unsafe {
                 type Fn<'this> = $(extern $($abi)?)? fn(*const (), $($arg_ty),*) $(-> $ret)?;
 
+                // `Self` may be less aligned than the vmt pointer it starts with.
                 #[allow(clippy::useless_transmute)]
-                ((**core::mem::transmute::<_, *const *const [Fn<'this>; $idx + 1]>(self))[Self::INDEX_OFFSET + $idx])(
+                ((*core::mem::transmute::<_, *const *const [Fn<'this>; $idx + 1]>(self).read_unaligned())[Self::INDEX_OFFSET + $idx])(
                     self as *const Self as _,
                     $($arg_name),*
                 )
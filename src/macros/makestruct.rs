@@ -4,6 +4,10 @@
 /// * For each struct declared within `makestruct` macro with specified parent there will be generated:
 ///     * Additional first field of parent type and name of `parent`
 ///     * Deref<Target = Parent> implementation
+///     * [`Inherits<Parent>`](crate::Inherits) implementation
+/// * `impl` only accepts interfaces. Other unsafe traits go after `unsafe impl`, which
+///   is how `RemoteStruct` is implemented. For `RemoteStruct` there is also a generated
+///   `<Name>Fields` trait with typed pointers to every field of a bound pointer to the struct.
 /// ```
 /// memflex::makestruct! {
 ///     // Attributes works as expected
@@ -61,6 +65,7 @@ macro_rules! makestruct {
         $(
             $( #[$($outter:tt)*] )*
             $vs:vis struct $sname:ident
+                $(unsafe impl $($uiface:ident),+ )?
                 $(impl $($iface:ident $((dyn $piface:ty))? ),* )?
                 $( : $pvis:vis $sparent:ty )?
             {
@@ -82,15 +87,24 @@ macro_rules! makestruct {
                 ),*
             }
 
+            $crate::__makestruct_unsafe_impl! {
+                [$($($uiface)+)?] $vs $sname { $($fname: $fty),* }
+            }
+
             $(
                 $(
                     unsafe impl $iface for $sname {
                         $( const INDEX_OFFSET: usize = <$piface>::FUNCTION_COUNT + <$piface>::INDEX_OFFSET; )?
                     }
+
+                    // Rejects traits that aren't interfaces, they need `unsafe impl`.
+                    const _: usize = <$sname as $iface>::FUNCTION_COUNT;
                 )*
             )?
 
             $(
+                unsafe impl $crate::Inherits<$sparent> for $sname {}
+
                 impl core::ops::Deref for $sname {
                     type Target = $sparent;
 
@@ -108,3 +122,44 @@ macro_rules! makestruct {
         )*
     };
 }
+
+#[macro_export]
+#[doc(hidden)]
+macro_rules! __makestruct_unsafe_impl {
+    ([] $vs:vis $sname:ident { $($fields:tt)* }) => {};
+    ([RemoteStruct $($rest:ident)*] $vs:vis $sname:ident { $($fname:ident: $fty:ty),* }) => {
+        unsafe impl $crate::external::RemoteStruct for $sname {}
+
+        $crate::paste! {
+            #[doc = "Typed pointers to the fields of a remote [`" $sname "`]."]
+            $vs trait [<$sname Fields>]<'p> {
+                $(
+                    #[doc = "Pointer to the `" $fname "` field."]
+                    fn $fname(&self) -> $crate::external::BoundPtr<'p, $fty>;
+                )*
+            }
+
+            impl<'p> [<$sname Fields>]<'p> for $crate::external::BoundPtr<'p, $sname> {
+                $(
+                    #[inline]
+                    fn $fname(&self) -> $crate::external::BoundPtr<'p, $fty> {
+                        self.field(core::mem::offset_of!($sname, $fname))
+                    }
+                )*
+            }
+        }
+
+        $crate::__makestruct_unsafe_impl! { [$($rest)*] $vs $sname { $($fname: $fty),* } }
+    };
+    ([$iface:ident $($rest:ident)*] $vs:vis $sname:ident { $($fields:tt)* }) => {
+        unsafe impl $iface for $sname {}
+
+        $crate::__makestruct_unsafe_impl! { [$($rest)*] $vs $sname { $($fields)* } }
+    };
+}
+
+/// Marks that `Self` starts with its parent `P`, implemented by [`makestruct`] for every
+/// struct with a parent.
+/// # Safety
+/// `Self` must contain `P` at offset 0.
+pub unsafe trait Inherits<P> {}
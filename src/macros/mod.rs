@@ -2,6 +2,7 @@ mod global;
 mod interface;
 mod makestruct;
 pub use global::*;
+pub use makestruct::*;
 mod function;
 pub use function::*;
 mod bitstruct;
@@ -1,5 +1,5 @@
-use crate::Matcher;
-use core::{ops::RangeInclusive, slice::from_raw_parts};
+use crate::{LocalMemory, Matcher, MemorySource};
+use core::ops::RangeInclusive;
 
 /// Creates an inmmutable slice from terminated array.
 /// # Safety
@@ -53,32 +53,19 @@ pub unsafe fn terminated_array_mut<'a, T: PartialEq>(mut first: *mut T, last: T)
 /// # Safety
 /// * All offsets must lead to valid memory addresses.
 #[inline]
-pub unsafe fn resolve_multilevel<T>(mut base: *const u8, offsets: &[usize]) -> *const T {
-    offsets.iter().enumerate().for_each(|(i, &o)| {
-        if i != offsets.len() - 1 {
-            base = base.add(o).cast::<*const u8>().read();
-        } else {
-            base = base.add(o);
-        }
-    });
-
-    base.cast()
+pub unsafe fn resolve_multilevel<T>(base: *const u8, offsets: &[usize]) -> *const T {
+    match LocalMemory::new().resolve_multilevel(base as usize, offsets) {
+        Ok(address) => address as _,
+        Err(_) => unreachable!(),
+    }
 }
 
 /// Resolves mutable multilevel pointer.
 /// # Safety
 /// * All offsets must lead to valid memory addresses.
 #[inline]
-pub unsafe fn resolve_multilevel_mut<T>(mut base: *mut u8, offsets: &[usize]) -> *mut T {
-    offsets.iter().enumerate().for_each(|(i, &o)| {
-        if i != offsets.len() - 1 {
-            base = base.add(o).cast::<*mut u8>().read();
-        } else {
-            base = base.add(o);
-        }
-    });
-
-    base.cast()
+pub unsafe fn resolve_multilevel_mut<T>(base: *mut u8, offsets: &[usize]) -> *mut T {
+    resolve_multilevel::<T>(base, offsets).cast_mut()
 }
 
 /// Searches for a pattern internally by start address and search length.
@@ -91,18 +78,12 @@ pub unsafe fn find_pattern(
     start: *const u8,
     len: usize,
 ) -> impl Iterator<Item = *const u8> {
+    static LOCAL: LocalMemory = unsafe { LocalMemory::new() };
     assert!(!start.is_null());
 
-    from_raw_parts::<u8>(start, len)
-        .windows(pat.len())
-        .enumerate()
-        .filter_map(move |(i, bytes)| {
-            if pat.matches(bytes) {
-                Some(start.add(i))
-            } else {
-                None
-            }
-        })
+    LOCAL
+        .find_pattern(pat, start as usize, len)
+        .map(|address| address as *const u8)
 }
 
 /// Searches for a pattern internally in a given range.
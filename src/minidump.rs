@@ -0,0 +1,398 @@
+use crate::{types::Protection, Matcher, MemorySource, MfError};
+use core::ops::Range;
+use std::{fs, path::Path};
+
+const SIGNATURE: u32 = 0x504D_444D; // MDMP
+
+const THREAD_LIST_STREAM: u32 = 3;
+const MODULE_LIST_STREAM: u32 = 4;
+const MEMORY_LIST_STREAM: u32 = 5;
+const SYSTEM_INFO_STREAM: u32 = 7;
+const MEMORY64_LIST_STREAM: u32 = 9;
+const MEMORY_INFO_LIST_STREAM: u32 = 16;
+/// Size of `MINIDUMP_MEMORY_INFO`, newer versions may append fields.
+const MEMORY_INFO_SIZE: usize = 48;
+
+/// `PROCESSOR_ARCHITECTURE_*` values of the system info stream.
+const ARCH_X86: u16 = 0;
+const ARCH_ARM: u16 = 5;
+const ARCH_AMD64: u16 = 9;
+const ARCH_ARM64: u16 = 12;
+
+/// Memory range stored in the dump.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MinidumpRegion {
+    /// Start
+    pub from: usize,
+    /// End
+    pub to: usize,
+    /// Protection, `None` if the dump has no memory info.
+    pub prot: Option<Protection>,
+    file_offset: usize,
+}
+
+/// Module loaded in the dumped process.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MinidumpModule {
+    /// Module's base
+    pub base: usize,
+    /// Module's size
+    pub size: usize,
+    /// Module's name
+    pub name: String,
+    /// Full path to the module.
+    pub path: String,
+}
+
+#[cfg(feature = "internal")]
+impl From<&MinidumpModule> for crate::types::ModuleInfoWithName {
+    fn from(m: &MinidumpModule) -> Self {
+        Self {
+            base: m.base as *const u8,
+            size: m.size,
+            name: m.name.clone(),
+        }
+    }
+}
+
+/// Thread of the dumped process.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MinidumpThread {
+    /// Id of the thread.
+    pub id: u32,
+    /// Address of the thread environment block.
+    pub teb: u64,
+    /// Range of the thread's stack.
+    pub stack: Range<usize>,
+    /// Raw `CONTEXT` structure of the dumped architecture.
+    pub context: Vec<u8>,
+    arch: Option<u16>,
+}
+
+impl MinidumpThread {
+    fn register(&self, offset: usize, wide: bool) -> Option<u64> {
+        if wide {
+            self.context
+                .get(offset..offset + 8)
+                .map(|b| u64::from_le_bytes(b.try_into().unwrap()))
+        } else {
+            self.context
+                .get(offset..offset + 4)
+                .map(|b| u32::from_le_bytes(b.try_into().unwrap()) as u64)
+        }
+    }
+
+    /// Returns the instruction pointer, `None` on unknown architectures.
+    pub fn instruction_pointer(&self) -> Option<u64> {
+        match self.arch? {
+            ARCH_AMD64 => self.register(248, true),
+            ARCH_X86 => self.register(184, false),
+            ARCH_ARM64 => self.register(264, true),
+            ARCH_ARM => self.register(64, false),
+            _ => None,
+        }
+    }
+
+    /// Returns the stack pointer, `None` on unknown architectures.
+    pub fn stack_pointer(&self) -> Option<u64> {
+        match self.arch? {
+            ARCH_AMD64 => self.register(152, true),
+            ARCH_X86 => self.register(196, false),
+            ARCH_ARM64 => self.register(256, true),
+            ARCH_ARM => self.register(56, false),
+            _ => None,
+        }
+    }
+}
+
+/// Windows minidump (`.dmp`) loaded in memory, read as the memory of the dumped process.
+/// Works on every platform.
+/// ```no_run
+/// # use memflex::{Minidump, MemorySource};
+/// let dump = Minidump::open("crash.dmp")?;
+/// let hit = dump
+///     .find_pattern_in_module(memflex::ida_pat!("48 8B 05 ? ? ? ?"), "game.exe")?
+///     .next();
+///
+/// let game = dump.find_module("game.exe")?;
+/// let health = dump.resolve_multilevel(game.base + 0x1234, &[0x10, 0x8])?;
+/// println!("{hit:X?} {}", dump.read::<f32>(health)?);
+/// # Ok::<_, memflex::MfError>(())
+/// ```
+#[derive(Debug, Clone)]
+pub struct Minidump {
+    data: Vec<u8>,
+    arch: Option<u16>,
+    regions: Vec<MinidumpRegion>,
+    modules: Vec<MinidumpModule>,
+    threads: Vec<MinidumpThread>,
+}
+
+fn bytes<const N: usize>(data: &[u8], offset: usize) -> crate::Result<[u8; N]> {
+    data.get(offset..offset.checked_add(N).ok_or(MfError::InvalidMinidump)?)
+        .map(|b| b.try_into().unwrap())
+        .ok_or(MfError::InvalidMinidump)
+}
+
+fn u16(data: &[u8], offset: usize) -> crate::Result<u16> {
+    bytes(data, offset).map(u16::from_le_bytes)
+}
+
+fn u32(data: &[u8], offset: usize) -> crate::Result<u32> {
+    bytes(data, offset).map(u32::from_le_bytes)
+}
+
+fn u64(data: &[u8], offset: usize) -> crate::Result<u64> {
+    bytes(data, offset).map(u64::from_le_bytes)
+}
+
+fn usize(data: &[u8], offset: usize) -> crate::Result<usize> {
+    usize::try_from(u64(data, offset)?).map_err(|_| MfError::InvalidMinidump)
+}
+
+/// Returns `len` bytes at `offset`.
+fn location(data: &[u8], offset: usize, len: usize) -> crate::Result<&[u8]> {
+    data.get(offset..offset.checked_add(len).ok_or(MfError::InvalidMinidump)?)
+        .ok_or(MfError::InvalidMinidump)
+}
+
+/// Converts `PAGE_*` constant to the protection.
+fn protection(protect: u32) -> Protection {
+    match protect & 0xFF {
+        0x02 => Protection::R,
+        0x04 | 0x08 => Protection::RW,
+        0x10 => Protection::X,
+        0x20 => Protection::RX,
+        0x40 | 0x80 => Protection::RWX,
+        _ => Protection::empty(),
+    }
+}
+
+impl Minidump {
+    /// Reads the minidump at `path`.
+    pub fn open(path: impl AsRef<Path>) -> crate::Result<Self> {
+        Self::parse(fs::read(path)?)
+    }
+
+    /// Parses the contents of a minidump.
+    /// # Behavior
+    /// Memory ranges cut off by the end of the file are shortened.
+    /// # Errors
+    /// [`MfError::InvalidMinidump`] if the data isn't a valid minidump.
+    pub fn parse(data: Vec<u8>) -> crate::Result<Self> {
+        let d = &data[..];
+        if u32(d, 0)? != SIGNATURE {
+            return Err(MfError::InvalidMinidump);
+        }
+
+        let mut streams = vec![];
+        let directory = u32(d, 12)? as usize;
+        for i in 0..u32(d, 8)? as usize {
+            let entry = directory + i * 12;
+            let len = u32(d, entry + 4)? as usize;
+            streams.push((
+                u32(d, entry)?,
+                location(d, u32(d, entry + 8)? as usize, len)?,
+            ));
+        }
+
+        let mut arch = None;
+        let mut regions = vec![];
+        let mut modules = vec![];
+        let mut threads = vec![];
+        // (range, protect) from the memory info stream.
+        let mut info = vec![];
+
+        let mut region = |from: usize, len: usize, offset: usize| -> crate::Result<()> {
+            regions.push(MinidumpRegion {
+                from,
+                to: from.checked_add(len).ok_or(MfError::InvalidMinidump)?,
+                prot: None,
+                file_offset: offset,
+            });
+            Ok(())
+        };
+
+        for (kind, s) in streams {
+            match kind {
+                SYSTEM_INFO_STREAM => arch = Some(u16(s, 0)?),
+                MEMORY_LIST_STREAM => {
+                    for i in 0..u32(s, 0)? as usize {
+                        let entry = 4 + i * 16;
+                        let offset = u32(s, entry + 12)? as usize;
+                        let len = (u32(s, entry + 8)? as usize).min(d.len().saturating_sub(offset));
+                        region(usize(s, entry)?, len, offset)?;
+                    }
+                }
+                MEMORY64_LIST_STREAM => {
+                    let mut offset = usize(s, 8)?;
+                    for i in 0..usize(s, 0)? {
+                        let entry = 16 + i * 16;
+                        let size = usize(s, entry + 8)?;
+                        let len = size.min(d.len().saturating_sub(offset));
+                        region(usize(s, entry)?, len, offset)?;
+                        offset = offset.saturating_add(size);
+                    }
+                }
+                MEMORY_INFO_LIST_STREAM => {
+                    let header = u32(s, 0)? as usize;
+                    let entry_size = u32(s, 4)? as usize;
+                    let count = usize(s, 8)?;
+
+                    let end = count
+                        .checked_mul(entry_size)
+                        .and_then(|len| len.checked_add(header));
+                    if entry_size < MEMORY_INFO_SIZE || end.is_none_or(|end| end > s.len()) {
+                        return Err(MfError::InvalidMinidump);
+                    }
+
+                    for i in 0..count {
+                        let entry = header + i * entry_size;
+                        let base = usize(s, entry)?;
+                        let size = usize(s, entry + 24)?;
+                        info.push((base..base.saturating_add(size), u32(s, entry + 36)?));
+                    }
+                }
+                MODULE_LIST_STREAM => {
+                    for i in 0..u32(s, 0)? as usize {
+                        let entry = 4 + i * 108;
+                        let name = u32(s, entry + 20)? as usize;
+                        let units = location(d, name + 4, u32(d, name)? as usize)?
+                            .chunks_exact(2)
+                            .map(|c| u16::from_le_bytes([c[0], c[1]]))
+                            .collect::<Vec<_>>();
+                        let path = String::from_utf16_lossy(&units);
+
+                        modules.push(MinidumpModule {
+                            base: usize(s, entry)?,
+                            size: u32(s, entry + 8)? as usize,
+                            name: path.rsplit(['\\', '/']).next().unwrap().to_owned(),
+                            path,
+                        });
+                    }
+                }
+                THREAD_LIST_STREAM => {
+                    for i in 0..u32(s, 0)? as usize {
+                        let entry = 4 + i * 48;
+                        let stack = usize(s, entry + 24)?;
+                        let context = location(
+                            d,
+                            u32(s, entry + 44)? as usize,
+                            u32(s, entry + 40)? as usize,
+                        )?;
+
+                        threads.push(MinidumpThread {
+                            id: u32(s, entry)?,
+                            teb: u64(s, entry + 16)?,
+                            stack: stack..stack.saturating_add(u32(s, entry + 32)? as usize),
+                            context: context.to_vec(),
+                            arch: None,
+                        });
+                    }
+                }
+                _ => {}
+            }
+        }
+
+        regions.sort_unstable_by_key(|r| r.from);
+        if regions.windows(2).any(|w| w[0].to > w[1].from) {
+            return Err(MfError::InvalidMinidump);
+        }
+
+        for region in &mut regions {
+            region.prot = info
+                .iter()
+                .find(|(range, _)| range.contains(&region.from))
+                .map(|(_, protect)| protection(*protect));
+        }
+        for thread in &mut threads {
+            thread.arch = arch;
+        }
+
+        Ok(Self {
+            data,
+            arch,
+            regions,
+            modules,
+            threads,
+        })
+    }
+
+    /// Memory ranges stored in the dump sorted by address.
+    #[inline]
+    pub fn regions(&self) -> &[MinidumpRegion] {
+        &self.regions
+    }
+
+    /// Modules loaded in the dumped process.
+    #[inline]
+    pub fn modules(&self) -> &[MinidumpModule] {
+        &self.modules
+    }
+
+    /// Threads of the dumped process.
+    #[inline]
+    pub fn threads(&self) -> &[MinidumpThread] {
+        &self.threads
+    }
+
+    /// Searches for the specified module in the dump.
+    /// # Case
+    /// Search is done case insensetive.
+    pub fn find_module(&self, name: &str) -> crate::Result<&MinidumpModule> {
+        self.modules
+            .iter()
+            .find(|m| m.name.eq_ignore_ascii_case(name))
+            .ok_or(MfError::ModuleNotFound)
+    }
+
+    /// Searches for a pattern in the specified module.
+    /// # Behavior
+    /// Only parts of the module stored in the dump are searched.
+    pub fn find_pattern_in_module<'a>(
+        &'a self,
+        pat: impl Matcher + 'a,
+        mod_name: &str,
+    ) -> crate::Result<impl Iterator<Item = usize> + 'a> {
+        let module = self.find_module(mod_name)?;
+
+        Ok(self.find_pattern(pat, module.base, module.size))
+    }
+
+    /// Returns the region that contains `address`.
+    fn region_at(&self, address: usize) -> Option<&MinidumpRegion> {
+        let i = self.regions.partition_point(|r| r.from <= address);
+        (i > 0 && address < self.regions[i - 1].to).then(|| &self.regions[i - 1])
+    }
+}
+
+impl MemorySource for Minidump {
+    /// Reads stored bytes, stopping at the end of the region that contains `address`.
+    fn read_buf(&self, address: usize, buf: &mut [u8]) -> crate::Result<usize> {
+        let region = self.region_at(address).ok_or(MfError::InvalidAddress)?;
+        let from = address - region.from;
+        let len = buf.len().min(region.to - address);
+
+        let start = region.file_offset + from;
+        buf[..len].copy_from_slice(&self.data[start..start + len]);
+        Ok(len)
+    }
+
+    /// Size of a pointer of the dumped system's architecture, `8` if it's unknown.
+    fn pointer_size(&self) -> crate::Result<usize> {
+        Ok(match self.arch {
+            Some(ARCH_X86 | ARCH_ARM) => 4,
+            _ => 8,
+        })
+    }
+
+    fn slice(&self, address: usize, len: usize) -> Option<&[u8]> {
+        let region = self.region_at(address)?;
+        if address.checked_add(len)? > region.to {
+            return None;
+        }
+
+        let start = region.file_offset + address - region.from;
+        Some(&self.data[start..start + len])
+    }
+}
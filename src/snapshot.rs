@@ -0,0 +1,297 @@
+use crate::{MemorySink, MemorySource, MfError};
+use core::ops::Range;
+use std::{
+    fs::File,
+    io::{self, BufReader, BufWriter, Read, Write},
+    path::Path,
+};
+
+#[cfg(feature = "external")]
+use crate::external::{MemoryRegion, OwnedProcess};
+
+const MAGIC: &[u8; 8] = b"MFSNAP01";
+
+/// Copy of a contiguous range of memory.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SnapshotRegion {
+    /// Address of the first byte.
+    pub address: usize,
+    /// Copied bytes.
+    pub data: Vec<u8>,
+}
+
+impl SnapshotRegion {
+    /// Range of addresses covered by the region.
+    #[inline]
+    pub fn range(&self) -> Range<usize> {
+        self.address..self.address + self.data.len()
+    }
+}
+
+/// Range of bytes that differ between two snapshots.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SnapshotChange {
+    /// Address of the first changed byte.
+    pub address: usize,
+    /// Bytes in the older snapshot.
+    pub old: Vec<u8>,
+    /// Bytes in the newer snapshot.
+    pub new: Vec<u8>,
+}
+
+/// Copy of selected memory regions taken at one moment.
+/// ```
+/// # use memflex::Snapshot;
+/// let mut health = [100u8, 0, 0, 0];
+/// let range = health.as_ptr() as usize..health.as_ptr() as usize + 4;
+///
+/// let before = unsafe { Snapshot::capture_local([range.clone()]) };
+/// health[0] = 90;
+/// let after = unsafe { Snapshot::capture_local([range.clone()]) };
+///
+/// let changes = before.diff(&after);
+/// assert_eq!(changes[0].address, range.start);
+/// assert_eq!((&changes[0].old[..], &changes[0].new[..]), (&[100][..], &[90][..]));
+/// ```
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Snapshot {
+    regions: Vec<SnapshotRegion>,
+}
+
+impl Snapshot {
+    /// Creates snapshot from the regions.
+    /// # Panics
+    /// If any of the regions overlap.
+    pub fn from_regions(mut regions: Vec<SnapshotRegion>) -> Self {
+        regions.sort_unstable_by_key(|r| r.address);
+        assert!(
+            regions.windows(2).all(|w| w[0].range().end <= w[1].address),
+            "Snapshot regions must not overlap"
+        );
+
+        Self { regions }
+    }
+
+    /// Copies memory of the current process.
+    /// # Safety
+    /// All ranges must be valid for reads.
+    pub unsafe fn capture_local(ranges: impl IntoIterator<Item = Range<usize>>) -> Self {
+        Self::from_regions(
+            ranges
+                .into_iter()
+                .map(|r| SnapshotRegion {
+                    address: r.start,
+                    data: core::slice::from_raw_parts(r.start as *const u8, r.len()).to_vec(),
+                })
+                .collect(),
+        )
+    }
+
+    /// Copies memory of the process.
+    /// # Errors
+    /// [`MfError::InvalidAddress`] if any of the ranges can't be read completely.
+    #[cfg(feature = "external")]
+    pub fn capture(
+        process: &OwnedProcess,
+        ranges: impl IntoIterator<Item = Range<usize>>,
+    ) -> crate::Result<Self> {
+        let mut regions = vec![];
+
+        for r in ranges {
+            let mut data = vec![0; r.len()];
+            if process.read_buf(r.start, &mut data[..])? != data.len() {
+                return Err(MfError::InvalidAddress);
+            }
+            regions.push(SnapshotRegion {
+                address: r.start,
+                data,
+            });
+        }
+
+        Ok(Self::from_regions(regions))
+    }
+
+    /// Copies every region of the process that passes the filter.
+    /// # Behavior
+    /// Regions that can't be read are skipped, partially readable ones are split into their
+    /// readable parts.
+    #[cfg(feature = "external")]
+    pub fn capture_regions(
+        process: &OwnedProcess,
+        filter: impl Fn(&MemoryRegion) -> bool,
+    ) -> crate::Result<Self> {
+        let mut regions = vec![];
+
+        for region in process.maps()? {
+            if !filter(&region) {
+                continue;
+            }
+
+            let mut data = vec![0; region.to - region.from];
+            let spans = process.read_buf_partial(region.from, &mut data[..], 0)?;
+
+            if let [span] = &spans[..] {
+                if span.len() == data.len() {
+                    regions.push(SnapshotRegion {
+                        address: region.from,
+                        data,
+                    });
+                    continue;
+                }
+            }
+            regions.extend(spans.into_iter().map(|s| SnapshotRegion {
+                address: s.start,
+                data: data[s.start - region.from..s.end - region.from].to_vec(),
+            }));
+        }
+
+        Ok(Self::from_regions(regions))
+    }
+
+    /// Regions of the snapshot sorted by address.
+    #[inline]
+    pub fn regions(&self) -> &[SnapshotRegion] {
+        &self.regions
+    }
+
+    /// Returns copied bytes at the range, `None` if it isn't entirely inside one region.
+    pub fn get(&self, range: Range<usize>) -> Option<&[u8]> {
+        let i = self.regions.partition_point(|r| r.address <= range.start);
+        let region = self.regions.get(i.checked_sub(1)?)?;
+
+        region
+            .data
+            .get(range.start - region.address..range.end.checked_sub(region.address)?)
+    }
+
+    /// Compares the snapshot with the newer one, returning ranges of bytes that differ.
+    /// # Behavior
+    /// Only memory present in both snapshots is compared.
+    pub fn diff(&self, newer: &Snapshot) -> Vec<SnapshotChange> {
+        let mut changes: Vec<SnapshotChange> = vec![];
+        let (mut i, mut j) = (0, 0);
+
+        while i < self.regions.len() && j < newer.regions.len() {
+            let (a, b) = (&self.regions[i], &newer.regions[j]);
+            let from = a.address.max(b.address);
+            let to = a.range().end.min(b.range().end);
+
+            for address in from..to {
+                let old = a.data[address - a.address];
+                let new = b.data[address - b.address];
+                if old == new {
+                    continue;
+                }
+
+                match changes.last_mut() {
+                    Some(c) if c.address + c.old.len() == address => {
+                        c.old.push(old);
+                        c.new.push(new);
+                    }
+                    _ => changes.push(SnapshotChange {
+                        address,
+                        old: vec![old],
+                        new: vec![new],
+                    }),
+                }
+            }
+
+            if a.range().end <= b.range().end {
+                i += 1;
+            } else {
+                j += 1;
+            }
+        }
+
+        changes
+    }
+
+    /// Saves the snapshot to the file.
+    pub fn save(&self, path: impl AsRef<Path>) -> crate::Result<()> {
+        let mut w = BufWriter::new(File::create(path)?);
+
+        w.write_all(MAGIC)?;
+        w.write_all(&(self.regions.len() as u64).to_le_bytes())?;
+        for r in &self.regions {
+            w.write_all(&(r.address as u64).to_le_bytes())?;
+            w.write_all(&(r.data.len() as u64).to_le_bytes())?;
+            w.write_all(&r.data)?;
+        }
+
+        Ok(w.flush()?)
+    }
+
+    /// Loads the snapshot previously saved with [`Snapshot::save`].
+    pub fn load(path: impl AsRef<Path>) -> crate::Result<Self> {
+        fn u64(r: &mut impl Read) -> io::Result<u64> {
+            let mut buf = [0; 8];
+            r.read_exact(&mut buf)?;
+            Ok(u64::from_le_bytes(buf))
+        }
+
+        let invalid = || io::Error::new(io::ErrorKind::InvalidData, "Invalid snapshot file");
+        let mut r = BufReader::new(File::open(path)?);
+
+        let mut magic = [0; 8];
+        r.read_exact(&mut magic)?;
+        if &magic != MAGIC {
+            return Err(invalid().into());
+        }
+
+        let mut regions = vec![];
+        for _ in 0..u64(&mut r)? {
+            let address = u64(&mut r)? as usize;
+            let len = u64(&mut r)?;
+            let mut data = vec![];
+            (&mut r).take(len).read_to_end(&mut data)?;
+            if data.len() as u64 != len || address.checked_add(data.len()).is_none() {
+                return Err(invalid().into());
+            }
+
+            regions.push(SnapshotRegion { address, data });
+        }
+
+        if regions.windows(2).any(|w| w[0].range().end > w[1].address) {
+            return Err(invalid().into());
+        }
+
+        Ok(Self { regions })
+    }
+}
+
+impl Snapshot {
+    /// Returns the index of the region that contains `address`.
+    fn region_at(&self, address: usize) -> Option<usize> {
+        let i = self.regions.partition_point(|r| r.address <= address);
+        (i > 0 && self.regions[i - 1].range().contains(&address)).then(|| i - 1)
+    }
+}
+
+impl MemorySource for Snapshot {
+    /// Reads copied bytes, stopping at the end of the region that contains `address`.
+    fn read_buf(&self, address: usize, buf: &mut [u8]) -> crate::Result<usize> {
+        let region = &self.regions[self.region_at(address).ok_or(MfError::InvalidAddress)?];
+        let from = address - region.address;
+        let len = buf.len().min(region.data.len() - from);
+
+        buf[..len].copy_from_slice(&region.data[from..from + len]);
+        Ok(len)
+    }
+
+    fn slice(&self, address: usize, len: usize) -> Option<&[u8]> {
+        self.get(address..address.checked_add(len)?)
+    }
+}
+
+impl MemorySink for Snapshot {
+    /// Overwrites copied bytes, stopping at the end of the region that contains `address`.
+    fn write_buf(&mut self, address: usize, buf: &[u8]) -> crate::Result<usize> {
+        let i = self.region_at(address).ok_or(MfError::InvalidAddress)?;
+        let region = &mut self.regions[i];
+        let from = address - region.address;
+        let len = buf.len().min(region.data.len() - from);
+
+        region.data[from..from + len].copy_from_slice(&buf[..len]);
+        Ok(len)
+    }
+}
@@ -0,0 +1,467 @@
+#[cfg(feature = "alloc")]
+extern crate alloc;
+#[cfg(feature = "alloc")]
+use alloc::{string::String, vec, vec::Vec};
+
+use crate::{Matcher, MfError};
+#[cfg(feature = "alloc")]
+use core::ops::Range;
+use core::{
+    mem::{size_of, MaybeUninit},
+    slice::{from_raw_parts, from_raw_parts_mut},
+};
+
+/// Memory is never requested across more than one boundary of this size.
+const PAGE: usize = 0x1000;
+
+/// Maximum length of a string read by [`MemorySource::read_str`].
+#[cfg(feature = "alloc")]
+pub(crate) const DEFAULT_MAX_STR_LEN: usize = 0x1000;
+
+/// Memory that can be read, no matter where it lives.
+/// ```
+/// # use memflex::{MemoryBuffer, MemorySource};
+/// let memory = MemoryBuffer::new(0x1000, *b"\x08\x10\0\0\0\0\0\0hello\0");
+/// assert_eq!(memory.read_str(0x1008)?, "hello");
+/// assert_eq!(memory.find_pattern(memflex::ida_pat!("6C 6C"), 0x1000, 14).next(), Some(0x100A));
+/// # Ok::<_, memflex::MfError>(())
+/// ```
+pub trait MemorySource {
+    /// Reads memory at `address` into the buffer, returning amount of bytes read.
+    fn read_buf(&self, address: usize, buf: &mut [u8]) -> crate::Result<usize>;
+
+    /// Size of a pointer in the memory.
+    #[inline]
+    fn pointer_size(&self) -> crate::Result<usize> {
+        Ok(size_of::<usize>())
+    }
+
+    /// Returns the memory as a slice, if it's addressable from the current process.
+    #[inline]
+    fn slice(&self, address: usize, len: usize) -> Option<&[u8]> {
+        _ = (address, len);
+        None
+    }
+
+    /// Fills the whole buffer with memory at `address`.
+    /// # Errors
+    /// [`MfError::InvalidAddress`] if only part of the buffer could be read.
+    fn read_exact(&self, address: usize, buf: &mut [u8]) -> crate::Result<()> {
+        if self.read_buf(address, buf)? == buf.len() {
+            Ok(())
+        } else {
+            Err(MfError::InvalidAddress)
+        }
+    }
+
+    /// Fills the whole buffer with memory at `address`, replacing bytes that can't be read
+    /// with `fill`. Returns address ranges that were read, sorted and not adjacent.
+    /// # Behavior
+    /// After a failed read memory is requested page by page until the unreadable part ends.
+    /// # Errors
+    /// [`MfError::ProcessDied`] if the memory belongs to a process that exited. Other
+    /// errors mark memory as unreadable.
+    /// ```
+    /// # use memflex::{MemoryBuffer, MemorySource};
+    /// let memory = MemoryBuffer::new(0x1000, [1; 4]);
+    /// let mut buf = [0; 8];
+    /// assert_eq!(memory.read_buf_partial(0x1000, &mut buf, 0xCC)?, [0x1000..0x1004]);
+    /// assert_eq!(buf, [1, 1, 1, 1, 0xCC, 0xCC, 0xCC, 0xCC]);
+    /// # Ok::<_, memflex::MfError>(())
+    /// ```
+    #[cfg(feature = "alloc")]
+    fn read_buf_partial(
+        &self,
+        address: usize,
+        buf: &mut [u8],
+        fill: u8,
+    ) -> crate::Result<Vec<Range<usize>>> {
+        let mut spans: Vec<Range<usize>> = vec![];
+        let mut done = 0;
+        // Whether the last read of the rest of the buffer has failed.
+        let mut hole = false;
+        while done < buf.len() {
+            let current = address.wrapping_add(done);
+            let len = if hole {
+                (PAGE - current % PAGE).min(buf.len() - done)
+            } else {
+                buf.len() - done
+            };
+
+            let read = match self.read_buf(current, &mut buf[done..done + len]) {
+                Err(MfError::ProcessDied) => return Err(MfError::ProcessDied),
+                Err(_) => 0,
+                Ok(read) => read.min(len),
+            };
+            match spans.last_mut() {
+                _ if read == 0 => {}
+                Some(last) if last.end == current => last.end += read,
+                _ => spans.push(current..current + read),
+            }
+
+            if read == len {
+                hole = false;
+            } else if hole {
+                // Rest of the page can't be read, the next one is read alone only if this
+                // one was entirely unreadable.
+                buf[done + read..done + len].fill(fill);
+                hole = read == 0;
+                done += len;
+                continue;
+            } else {
+                hole = true;
+            }
+            done += read;
+        }
+
+        Ok(spans)
+    }
+
+    /// Reads a value of type `T` at `address`.
+    fn read<T: Copy>(&self, address: usize) -> crate::Result<T>
+    where
+        Self: Sized,
+    {
+        let mut value = MaybeUninit::<T>::uninit();
+        unsafe {
+            self.read_exact(
+                address,
+                from_raw_parts_mut(value.as_mut_ptr().cast::<u8>(), size_of::<T>()),
+            )?;
+            Ok(value.assume_init())
+        }
+    }
+
+    /// Reads a pointer of the memory's width, see [`MemorySource::pointer_size`].
+    fn read_pointer(&self, address: usize) -> crate::Result<usize> {
+        match self.pointer_size()? {
+            4 => {
+                let mut buf = [0; 4];
+                self.read_exact(address, &mut buf)?;
+                Ok(u32::from_ne_bytes(buf) as usize)
+            }
+            _ => {
+                let mut buf = [0; size_of::<usize>()];
+                self.read_exact(address, &mut buf)?;
+                Ok(usize::from_ne_bytes(buf))
+            }
+        }
+    }
+
+    /// Resolves multilevel pointer.
+    /// # Behavior
+    /// Every offset but the last one is followed by reading a pointer.
+    fn resolve_multilevel(&self, mut base: usize, offsets: &[usize]) -> crate::Result<usize> {
+        for (i, &o) in offsets.iter().enumerate() {
+            if i != offsets.len() - 1 {
+                base = self.read_pointer(base.wrapping_add(o))?;
+            } else {
+                base = base.wrapping_add(o);
+            }
+        }
+
+        Ok(base)
+    }
+
+    /// Finds all occurences of the pattern that lie entirely within `start..start + len`.
+    /// # Behavior
+    /// Memory that can't be read is skipped. Patterns longer than 4096 bytes are never found
+    /// unless the memory is addressable directly.
+    fn find_pattern<M: Matcher>(&self, pat: M, start: usize, len: usize) -> PatternIter<'_, Self, M>
+    where
+        Self: Sized,
+    {
+        PatternIter {
+            direct: self.slice(start, len),
+            src: self,
+            pat,
+            end: start.saturating_add(len),
+            chunk: start,
+            chunk_len: 0,
+            index: 0,
+            buf: [0; PAGE * 2],
+        }
+    }
+
+    /// Reads code units of `width` bytes until a zero one or until `max_len` of them were read.
+    /// # Behavior
+    /// Memory is requested in chunks that never cross a page boundary, so the string may end
+    /// right before unreadable memory. Returned bytes don't include the terminator.
+    #[cfg(feature = "alloc")]
+    fn read_terminated(
+        &self,
+        address: usize,
+        width: usize,
+        max_len: usize,
+    ) -> crate::Result<Vec<u8>> {
+        let max_bytes = max_len.saturating_mul(width);
+
+        let mut out = vec![];
+        // Amount of bytes checked for the terminator.
+        let mut checked = 0;
+
+        while out.len() < max_bytes {
+            let current = address + out.len();
+            let chunk = (PAGE - current % PAGE).min(max_bytes - out.len());
+
+            let start = out.len();
+            out.resize(start + chunk, 0);
+            let read = self.read_buf(current, &mut out[start..])?;
+            out.truncate(start + read);
+            if read == 0 {
+                return Err(MfError::InvalidAddress);
+            }
+
+            while checked + width <= out.len() {
+                if out[checked..checked + width].iter().all(|b| *b == 0) {
+                    out.truncate(checked);
+                    return Ok(out);
+                }
+                checked += width;
+            }
+        }
+
+        out.truncate(checked);
+        Ok(out)
+    }
+
+    /// Reads bytes of a zero terminated string at `address`, up to `max_len` bytes.
+    #[cfg(feature = "alloc")]
+    fn read_str_bytes(&self, address: usize, max_len: usize) -> crate::Result<Vec<u8>> {
+        self.read_terminated(address, 1, max_len)
+    }
+
+    /// Reads zero terminated UTF-8 string at `address`.
+    /// # Behavior
+    /// Reads at most 4096 bytes, use [`MemorySource::read_str_bounded`] for longer strings.
+    /// # Errors
+    /// [`MfError::UnterminatedString`] if there is no terminator within the 4096 bytes.
+    #[cfg(feature = "alloc")]
+    fn read_str(&self, address: usize) -> crate::Result<String> {
+        // One more byte tells a string of the maximum length from a longer one.
+        let bytes = self.read_str_bytes(address, DEFAULT_MAX_STR_LEN + 1)?;
+        if bytes.len() > DEFAULT_MAX_STR_LEN {
+            return Err(MfError::UnterminatedString);
+        }
+
+        String::from_utf8(bytes).map_err(|_| MfError::InvalidString)
+    }
+
+    /// Reads zero terminated UTF-8 string at `address`, up to `max_len` bytes.
+    #[cfg(feature = "alloc")]
+    fn read_str_bounded(&self, address: usize, max_len: usize) -> crate::Result<String> {
+        String::from_utf8(self.read_str_bytes(address, max_len)?)
+            .map_err(|_| MfError::InvalidString)
+    }
+
+    /// Reads zero terminated string at `address`, up to `max_len` bytes,
+    /// replacing invalid UTF-8 sequences.
+    #[cfg(feature = "alloc")]
+    fn read_str_lossy(&self, address: usize, max_len: usize) -> crate::Result<String> {
+        Ok(String::from_utf8_lossy(&self.read_str_bytes(address, max_len)?).into_owned())
+    }
+}
+
+/// Memory that can be written, no matter where it lives.
+pub trait MemorySink {
+    /// Writes the buffer at `address`, returning amount of bytes written.
+    fn write_buf(&mut self, address: usize, buf: &[u8]) -> crate::Result<usize>;
+
+    /// Writes the whole buffer at `address`.
+    /// # Errors
+    /// [`MfError::InvalidAddress`] if only part of the buffer could be written.
+    fn write_exact(&mut self, address: usize, buf: &[u8]) -> crate::Result<()> {
+        if self.write_buf(address, buf)? == buf.len() {
+            Ok(())
+        } else {
+            Err(MfError::InvalidAddress)
+        }
+    }
+
+    /// Writes the value at `address`.
+    fn write<T: Copy>(&mut self, address: usize, value: &T) -> crate::Result<()>
+    where
+        Self: Sized,
+    {
+        self.write_exact(address, unsafe {
+            from_raw_parts((value as *const T).cast::<u8>(), size_of::<T>())
+        })
+    }
+}
+
+/// Iterator over addresses of a pattern, returned by [`MemorySource::find_pattern`].
+pub struct PatternIter<'a, S, M> {
+    src: &'a S,
+    pat: M,
+    /// Memory accessed without copying.
+    direct: Option<&'a [u8]>,
+    end: usize,
+    /// Address of the first byte of `buf` or `direct`.
+    chunk: usize,
+    chunk_len: usize,
+    /// Index of the next candidate in the chunk.
+    index: usize,
+    buf: [u8; PAGE * 2],
+}
+
+impl<S: MemorySource, M: Matcher> Iterator for PatternIter<'_, S, M> {
+    type Item = usize;
+
+    fn next(&mut self) -> Option<usize> {
+        let width = self.pat.len();
+
+        loop {
+            let data = match self.direct {
+                Some(direct) => direct,
+                None => &self.buf[..self.chunk_len],
+            };
+
+            while self.index + width <= data.len() {
+                self.index += 1;
+                if self
+                    .pat
+                    .matches(&data[self.index - 1..self.index - 1 + width])
+                {
+                    return Some(self.chunk + self.index - 1);
+                }
+            }
+
+            if self.direct.is_some() {
+                return None;
+            }
+
+            // First candidate that wasn't checked yet.
+            let next = self.chunk + self.index;
+            if next.checked_add(width).is_none_or(|e| e > self.end) {
+                return None;
+            }
+
+            // Up to the end of the next page, so patterns crossing the boundary are found.
+            let len = (PAGE * 2 - next % PAGE).min(self.end - next);
+            let read = self.src.read_buf(next, &mut self.buf[..len]).unwrap_or(0);
+
+            if read < width.max(1) {
+                // Skip unreadable page.
+                self.chunk = (next / PAGE + 1) * PAGE;
+                self.chunk_len = 0;
+            } else {
+                self.chunk = next;
+                self.chunk_len = read;
+            }
+            self.index = 0;
+        }
+    }
+}
+
+/// Memory of the current process.
+#[derive(Debug, Clone, Copy)]
+pub struct LocalMemory(());
+
+impl LocalMemory {
+    /// Creates accessor to the memory of the current process.
+    /// # Safety
+    /// Every address read or written through it must be valid for reads or writes.
+    #[inline]
+    pub const unsafe fn new() -> Self {
+        Self(())
+    }
+}
+
+impl MemorySource for LocalMemory {
+    #[inline]
+    fn read_buf(&self, address: usize, buf: &mut [u8]) -> crate::Result<usize> {
+        unsafe {
+            core::ptr::copy_nonoverlapping(address as *const u8, buf.as_mut_ptr(), buf.len());
+        }
+        Ok(buf.len())
+    }
+
+    #[inline]
+    fn slice(&self, address: usize, len: usize) -> Option<&[u8]> {
+        unsafe { Some(from_raw_parts(address as *const u8, len)) }
+    }
+}
+
+impl MemorySink for LocalMemory {
+    #[inline]
+    fn write_buf(&mut self, address: usize, buf: &[u8]) -> crate::Result<usize> {
+        unsafe {
+            core::ptr::copy_nonoverlapping(buf.as_ptr(), address as *mut u8, buf.len());
+        }
+        Ok(buf.len())
+    }
+}
+
+/// Buffer pretending to be memory that starts at `base`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MemoryBuffer<B> {
+    /// Address of the first byte.
+    pub base: usize,
+    /// Contents of the memory.
+    pub data: B,
+}
+
+impl<B> MemoryBuffer<B> {
+    /// Creates new buffer at `base`.
+    #[inline]
+    pub const fn new(base: usize, data: B) -> Self {
+        Self { base, data }
+    }
+}
+
+impl<B: AsRef<[u8]>> MemorySource for MemoryBuffer<B> {
+    fn read_buf(&self, address: usize, buf: &mut [u8]) -> crate::Result<usize> {
+        let data = self.data.as_ref();
+        let from = address
+            .checked_sub(self.base)
+            .filter(|o| *o < data.len())
+            .ok_or(MfError::InvalidAddress)?;
+
+        let len = buf.len().min(data.len() - from);
+        buf[..len].copy_from_slice(&data[from..from + len]);
+        Ok(len)
+    }
+
+    fn slice(&self, address: usize, len: usize) -> Option<&[u8]> {
+        let from = address.checked_sub(self.base)?;
+        self.data.as_ref().get(from..from.checked_add(len)?)
+    }
+}
+
+impl<B: AsMut<[u8]>> MemorySink for MemoryBuffer<B> {
+    fn write_buf(&mut self, address: usize, buf: &[u8]) -> crate::Result<usize> {
+        let data = self.data.as_mut();
+        let from = address
+            .checked_sub(self.base)
+            .filter(|o| *o < data.len())
+            .ok_or(MfError::InvalidAddress)?;
+
+        let len = buf.len().min(data.len() - from);
+        data[from..from + len].copy_from_slice(&buf[..len]);
+        Ok(len)
+    }
+}
+
+impl<S: MemorySource + ?Sized> MemorySource for &S {
+    #[inline]
+    fn read_buf(&self, address: usize, buf: &mut [u8]) -> crate::Result<usize> {
+        (**self).read_buf(address, buf)
+    }
+
+    #[inline]
+    fn pointer_size(&self) -> crate::Result<usize> {
+        (**self).pointer_size()
+    }
+
+    #[inline]
+    fn slice(&self, address: usize, len: usize) -> Option<&[u8]> {
+        (**self).slice(address, len)
+    }
+}
+
+impl<S: MemorySink + ?Sized> MemorySink for &mut S {
+    #[inline]
+    fn write_buf(&mut self, address: usize, buf: &[u8]) -> crate::Result<usize> {
+        (**self).write_buf(address, buf)
+    }
+}
@@ -42,6 +42,18 @@ pub struct VmtPtr {
 }
 
 impl VmtPtr {
+    /// Creates a pointer to the table.
+    #[inline]
+    pub const fn new(vmt: *const usize) -> Self {
+        Self { vmt }
+    }
+
+    /// Returns the address of the first function in the table.
+    #[inline]
+    pub const fn as_ptr(&self) -> *const usize {
+        self.vmt
+    }
+
     /// Creates a slice of all functions in vmt until meets `0`.
     /// # Safety
     /// * `self` must be a valid pointer with at most `usize::MAX - 1` zero terminated elements.
@@ -0,0 +1,365 @@
+/// Maximum length of an instruction.
+pub const MAX_LEN: usize = 15;
+
+crate::bitflags! {
+    /// Prefixes of an instruction.
+    #[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+    pub struct Prefixes : u16 {
+        /// `F0`
+        const LOCK = 1 << 0;
+        /// `F2`
+        const REPNE = 1 << 1;
+        /// `F3`
+        const REP = 1 << 2;
+        /// `2E`, also a branch not taken hint.
+        const CS = 1 << 3;
+        /// `36`
+        const SS = 1 << 4;
+        /// `3E`, also a branch taken hint.
+        const DS = 1 << 5;
+        /// `26`
+        const ES = 1 << 6;
+        /// `64`
+        const FS = 1 << 7;
+        /// `65`
+        const GS = 1 << 8;
+        /// `66`
+        const OPERAND_SIZE = 1 << 9;
+        /// `67`
+        const ADDRESS_SIZE = 1 << 10;
+        /// `40`-`4F`, see [`Instruction::rex`].
+        const REX = 1 << 11;
+        /// `C4` or `C5`
+        const VEX = 1 << 12;
+        /// `62`
+        const EVEX = 1 << 13;
+    }
+}
+
+/// Opcode map an instruction belongs to.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum OpcodeMap {
+    /// One byte opcodes.
+    Primary,
+    /// Opcodes escaped with `0F`.
+    Escape0F,
+    /// Opcodes escaped with `0F 38`.
+    Escape0F38,
+    /// Opcodes escaped with `0F 3A`.
+    Escape0F3A,
+}
+
+/// Location of a field inside an instruction.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Field {
+    /// Offset from the first byte of the instruction.
+    pub offset: usize,
+    /// Size in bytes.
+    pub size: usize,
+}
+
+impl Field {
+    /// Reads the field from the instruction's bytes as a sign extended value.
+    /// Returns `None` if `code` is too short or the field is 3 bytes long, like in `enter`.
+    pub fn value(&self, code: &[u8]) -> Option<i64> {
+        let bytes = code.get(self.offset..self.offset + self.size)?;
+        Some(match *bytes {
+            [a] => a as i8 as i64,
+            [a, b] => i16::from_le_bytes([a, b]) as i64,
+            [a, b, c, d] => i32::from_le_bytes([a, b, c, d]) as i64,
+            [a, b, c, d, e, f, g, h] => i64::from_le_bytes([a, b, c, d, e, f, g, h]),
+            _ => return None,
+        })
+    }
+}
+
+/// Decoded x86-64 instruction.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Instruction {
+    /// Length in bytes.
+    pub len: usize,
+    /// Prefixes, VEX and EVEX included.
+    pub prefixes: Prefixes,
+    /// REX prefix.
+    pub rex: Option<u8>,
+    /// Opcode map.
+    pub map: OpcodeMap,
+    /// Last byte of the opcode.
+    pub opcode: u8,
+    /// ModRM byte.
+    pub modrm: Option<u8>,
+    /// SIB byte.
+    pub sib: Option<u8>,
+    /// Memory displacement.
+    pub disp: Option<Field>,
+    /// Immediate, for relative branches it's the branch offset.
+    pub imm: Option<Field>,
+    /// Displacement is relative to the address of the next instruction.
+    pub rip_relative: bool,
+    /// Immediate is an offset relative to the address of the next instruction,
+    /// i.e. `jmp`, `jcc`, `call`, `loop`, `jrcxz` and `xbegin`.
+    pub relative_branch: bool,
+}
+
+impl Instruction {
+    /// Returns the address a relative branch goes to or a RIP-relative operand points to,
+    /// if the instruction at `address` has either. `code` starts with the instruction.
+    pub fn target(&self, address: usize, code: &[u8]) -> Option<usize> {
+        let field = match (self.relative_branch, self.rip_relative) {
+            (true, _) => self.imm?,
+            (_, true) => self.disp?,
+            _ => return None,
+        };
+
+        Some((address + self.len).wrapping_add_signed(field.value(code)? as isize))
+    }
+}
+
+/// Size of an immediate.
+#[derive(Clone, Copy)]
+enum Imm {
+    None,
+    Fixed(usize),
+    /// 16 bits with operand size prefix, 32 bits otherwise.
+    Z,
+    /// 64 bits with `REX.W`, otherwise like [`Imm::Z`].
+    V,
+    /// Address of `mov` with memory offset.
+    Moffs,
+    /// Branch offset.
+    Rel(usize),
+    /// Like [`Imm::Fixed`] only for `test`, other instructions of the group have none.
+    Test(usize),
+    /// Like [`Imm::Z`] only for `test`, other instructions of the group have none.
+    TestZ,
+}
+
+/// Returns whether an opcode has ModRM byte and its immediate, `None` for invalid opcodes.
+fn primary(op: u8) -> Option<(bool, Imm)> {
+    Some(match op {
+        0x06 | 0x07 | 0x0E | 0x16 | 0x17 | 0x1E | 0x1F | 0x27 | 0x2F | 0x37 | 0x3F => return None,
+        0x00..=0x3F => match op & 7 {
+            0..=3 => (true, Imm::None),
+            4 => (false, Imm::Fixed(1)),
+            5 => (false, Imm::Z),
+            _ => return None,
+        },
+        0x50..=0x5F => (false, Imm::None),
+        0x63 => (true, Imm::None),
+        0x68 => (false, Imm::Z),
+        0x69 => (true, Imm::Z),
+        0x6A => (false, Imm::Fixed(1)),
+        0x6B => (true, Imm::Fixed(1)),
+        0x6C..=0x6F => (false, Imm::None),
+        0x70..=0x7F => (false, Imm::Rel(1)),
+        0x80 | 0x83 => (true, Imm::Fixed(1)),
+        0x81 => (true, Imm::Z),
+        0x84..=0x8F => (true, Imm::None),
+        0x90..=0x99 | 0x9B..=0x9F => (false, Imm::None),
+        0xA0..=0xA3 => (false, Imm::Moffs),
+        0xA8 => (false, Imm::Fixed(1)),
+        0xA9 => (false, Imm::Z),
+        0xA4..=0xAF => (false, Imm::None),
+        0xB0..=0xB7 => (false, Imm::Fixed(1)),
+        0xB8..=0xBF => (false, Imm::V),
+        0xC0 | 0xC1 | 0xC6 => (true, Imm::Fixed(1)),
+        0xC2 | 0xCA => (false, Imm::Fixed(2)),
+        0xC3 | 0xC9 | 0xCB | 0xCC | 0xCF => (false, Imm::None),
+        0xC7 => (true, Imm::Z),
+        0xC8 => (false, Imm::Fixed(3)),
+        0xCD => (false, Imm::Fixed(1)),
+        0xD0..=0xD3 | 0xD8..=0xDF => (true, Imm::None),
+        0xD7 => (false, Imm::None),
+        0xE0..=0xE3 | 0xEB => (false, Imm::Rel(1)),
+        0xE4..=0xE7 => (false, Imm::Fixed(1)),
+        0xE8 | 0xE9 => (false, Imm::Rel(4)),
+        0xEC..=0xEF | 0xF1 | 0xF4 | 0xF5 | 0xF8..=0xFD => (false, Imm::None),
+        0xF6 => (true, Imm::Test(1)),
+        0xF7 => (true, Imm::TestZ),
+        0xFE | 0xFF => (true, Imm::None),
+        _ => return None,
+    })
+}
+
+/// Like [`primary`] for opcodes escaped with `0F`.
+fn secondary(op: u8) -> Option<(bool, Imm)> {
+    Some(match op {
+        0x04 | 0x0A | 0x0C | 0x0F | 0x24..=0x27 | 0x36 | 0x38..=0x3F | 0x7A | 0x7B | 0xFF => {
+            return None
+        }
+        0x05..=0x09 | 0x0B | 0x0E | 0x30..=0x35 | 0x37 | 0x77 => (false, Imm::None),
+        0xA0..=0xA2 | 0xA8..=0xAA | 0xC8..=0xCF => (false, Imm::None),
+        0x80..=0x8F => (false, Imm::Rel(4)),
+        0x70..=0x73 | 0xA4 | 0xAC | 0xBA | 0xC2 | 0xC4..=0xC6 => (true, Imm::Fixed(1)),
+        _ => (true, Imm::None),
+    })
+}
+
+/// Decodes the instruction at the start of `code`, `None` if it's invalid or truncated.
+/// # Behavior
+/// Only the 64-bit mode is supported, opcodes that are invalid in it are rejected.
+/// 3DNow! and XOP instructions aren't supported.
+/// ```
+/// use memflex::x86::{decode, OpcodeMap};
+///
+/// // mov rax, [rip + 0x10]
+/// let code = [0x48, 0x8B, 0x05, 0x10, 0x00, 0x00, 0x00];
+/// let ins = decode(&code).unwrap();
+/// assert_eq!(ins.len, 7);
+/// assert_eq!((ins.map, ins.opcode, ins.rex), (OpcodeMap::Primary, 0x8B, Some(0x48)));
+/// assert!(ins.rip_relative);
+/// assert_eq!(ins.target(0x1000, &code), Some(0x1017));
+/// ```
+pub fn decode(code: &[u8]) -> Option<Instruction> {
+    let code = &code[..code.len().min(MAX_LEN)];
+    let mut i = 0;
+    let mut prefixes = Prefixes::empty();
+
+    loop {
+        prefixes |= match *code.get(i)? {
+            0xF0 => Prefixes::LOCK,
+            0xF2 => Prefixes::REPNE,
+            0xF3 => Prefixes::REP,
+            0x2E => Prefixes::CS,
+            0x36 => Prefixes::SS,
+            0x3E => Prefixes::DS,
+            0x26 => Prefixes::ES,
+            0x64 => Prefixes::FS,
+            0x65 => Prefixes::GS,
+            0x66 => Prefixes::OPERAND_SIZE,
+            0x67 => Prefixes::ADDRESS_SIZE,
+            _ => break,
+        };
+        i += 1;
+    }
+
+    let mut rex = None;
+    if let byte @ 0x40..=0x4F = *code.get(i)? {
+        prefixes |= Prefixes::REX;
+        rex = Some(byte);
+        i += 1;
+    }
+    let rex_w = rex.is_some_and(|r| r & 8 != 0);
+
+    let (map, opcode, modrm, imm) = match *code.get(i)? {
+        // VEX and EVEX, opcode maps are selected by the prefix.
+        vex @ (0xC4 | 0xC5 | 0x62) => {
+            let (len, map) = match vex {
+                0xC5 => (2, 1),
+                0xC4 => (3, *code.get(i + 1)? & 0x1F),
+                _ => (4, *code.get(i + 1)? & 0x07),
+            };
+            prefixes |= if vex == 0x62 {
+                Prefixes::EVEX
+            } else {
+                Prefixes::VEX
+            };
+            i += len;
+            let opcode = *code.get(i)?;
+            match map {
+                1 => {
+                    let (modrm, imm) = secondary(opcode)?;
+                    (OpcodeMap::Escape0F, opcode, modrm, imm)
+                }
+                2 => (OpcodeMap::Escape0F38, opcode, true, Imm::None),
+                3 => (OpcodeMap::Escape0F3A, opcode, true, Imm::Fixed(1)),
+                _ => return None,
+            }
+        }
+        0x0F => {
+            i += 1;
+            match *code.get(i)? {
+                0x38 => {
+                    i += 1;
+                    (OpcodeMap::Escape0F38, *code.get(i)?, true, Imm::None)
+                }
+                0x3A => {
+                    i += 1;
+                    (OpcodeMap::Escape0F3A, *code.get(i)?, true, Imm::Fixed(1))
+                }
+                opcode => {
+                    let (modrm, imm) = secondary(opcode)?;
+                    (OpcodeMap::Escape0F, opcode, modrm, imm)
+                }
+            }
+        }
+        opcode => {
+            let (modrm, imm) = primary(opcode)?;
+            (OpcodeMap::Primary, opcode, modrm, imm)
+        }
+    };
+    i += 1;
+
+    let (mut modrm, mut sib, mut disp) = (modrm.then_some(0), None, None);
+    let mut rip_relative = false;
+    let mut reg = 0;
+    let mut xbegin = false;
+    if let Some(modrm) = &mut modrm {
+        *modrm = *code.get(i)?;
+        let modrm = *modrm;
+        i += 1;
+        let (mode, rm) = (modrm >> 6, modrm & 7);
+        reg = (modrm >> 3) & 7;
+        xbegin = map == OpcodeMap::Primary && opcode == 0xC7 && modrm == 0xF8;
+
+        let mut size = match mode {
+            0 if rm == 5 => {
+                rip_relative = true;
+                4
+            }
+            1 => 1,
+            2 => 4,
+            _ => 0,
+        };
+        if mode != 3 && rm == 4 {
+            let byte = *code.get(i)?;
+            sib = Some(byte);
+            i += 1;
+            if mode == 0 && byte & 7 == 5 {
+                size = 4;
+            }
+        }
+
+        if size != 0 {
+            disp = Some(Field { offset: i, size });
+            i += size;
+        }
+    }
+
+    let z = if prefixes.contains(Prefixes::OPERAND_SIZE) {
+        2
+    } else {
+        4
+    };
+    let (size, relative_branch) = match imm {
+        Imm::None => (0, false),
+        Imm::Fixed(size) => (size, false),
+        Imm::Z => (z, xbegin),
+        Imm::V => (if rex_w { 8 } else { z }, false),
+        Imm::Moffs if prefixes.contains(Prefixes::ADDRESS_SIZE) => (4, false),
+        Imm::Moffs => (8, false),
+        Imm::Rel(size) => (size, true),
+        Imm::Test(size) => (if reg < 2 { size } else { 0 }, false),
+        Imm::TestZ => (if reg < 2 { z } else { 0 }, false),
+    };
+
+    let imm = (size != 0).then_some(Field { offset: i, size });
+    i += size;
+    if i > code.len() {
+        return None;
+    }
+
+    Some(Instruction {
+        len: i,
+        prefixes,
+        rex,
+        map,
+        opcode,
+        modrm,
+        sib,
+        disp,
+        imm,
+        rip_relative,
+        relative_branch,
+    })
+}
@@ -0,0 +1,133 @@
+#![cfg(all(target_os = "linux", feature = "external"))]
+
+use memflex::{
+    external::{find_process_by_id, ProcessState},
+    ida_pat, CoreDump, MemorySource, MfError,
+};
+use std::{hint::black_box, process::Command, time::Duration};
+
+fn temp_path(name: &str) -> std::path::PathBuf {
+    std::env::temp_dir().join(format!("memflex-{name}-{}", std::process::id()))
+}
+
+#[test]
+fn test_dump_current_process() {
+    let value = black_box(Box::new(*b"memflex core marker\0"));
+    let ptr = black_box(Box::new(value.as_ptr() as usize));
+
+    let p = find_process_by_id(std::process::id()).unwrap();
+    let path = temp_path("core-self");
+    p.dump_core(&path).unwrap();
+    let core = CoreDump::open(&path).unwrap();
+    std::fs::remove_file(&path).unwrap();
+
+    let address = core
+        .resolve_multilevel(&*ptr as *const usize as usize, &[0, 0])
+        .unwrap();
+    assert_eq!(address, value.as_ptr() as usize);
+    assert_eq!(core.read_str(address).unwrap(), "memflex core marker");
+    assert_eq!(
+        core.find_pattern(ida_pat!("63 6F 72 65 20 6D"), address, 20)
+            .next(),
+        Some(address + 8)
+    );
+
+    let exe = std::env::current_exe().unwrap();
+    let exe = exe.to_str().unwrap();
+    assert!(core.files().iter().any(|f| f.path == exe));
+    assert!(core
+        .maps()
+        .iter()
+        .any(|r| r.path.as_deref() == Some(exe) && r.prot.execute() && r.stored > 0));
+
+    let thread = &core.threads()[0];
+    assert_eq!(thread.id, std::process::id());
+    assert!(thread.registers.is_empty());
+}
+
+#[test]
+fn test_dump_partially_readable() {
+    // Pages past the end of the file can't be read.
+    let file_path = temp_path("core-short-file");
+    std::fs::write(&file_path, [0xAB_u8; 0x1000]).unwrap();
+    let file = std::fs::File::open(&file_path).unwrap();
+    let map = unsafe {
+        libc::mmap(
+            core::ptr::null_mut(),
+            0x3000,
+            libc::PROT_READ,
+            libc::MAP_PRIVATE,
+            std::os::fd::AsRawFd::as_raw_fd(&file),
+            0,
+        )
+    };
+    assert_ne!(map, libc::MAP_FAILED);
+    let map = map as usize;
+
+    let p = find_process_by_id(std::process::id()).unwrap();
+    let path = temp_path("core-partial");
+    let result = p.dump_core(&path);
+    unsafe { libc::munmap(map as _, 0x3000) };
+    std::fs::remove_file(&file_path).unwrap();
+    result.unwrap();
+
+    let core = CoreDump::open(&path).unwrap();
+    std::fs::remove_file(&path).unwrap();
+
+    let region = core.maps().iter().find(|r| r.from == map).unwrap();
+    assert_eq!(region.stored, 0x3000);
+    assert_eq!(core.read::<u8>(map).unwrap(), 0xAB);
+    assert_eq!(core.read::<u8>(map + 0x2FFF).unwrap(), 0);
+}
+
+#[test]
+fn test_dump_other_process() {
+    let mut child = Command::new("/bin/sleep").arg("30").spawn().unwrap();
+    std::thread::sleep(Duration::from_millis(50));
+
+    let p = find_process_by_id(child.id()).unwrap();
+    let path = temp_path("core-child");
+    let result = p.dump_core(&path);
+    let state = p.state();
+
+    child.kill().unwrap();
+    child.wait().unwrap();
+    result.unwrap();
+    // Not left stopped after the dump.
+    assert!(!matches!(
+        state.unwrap(),
+        ProcessState::Stopped | ProcessState::TracingStop
+    ));
+
+    let core = CoreDump::open(&path).unwrap();
+    std::fs::remove_file(&path).unwrap();
+
+    assert_eq!(core.threads().len(), 1);
+    let thread = &core.threads()[0];
+    assert_eq!(thread.id, child.id());
+    assert!(!thread.registers.is_empty());
+
+    let ip = thread.instruction_pointer().unwrap() as usize;
+    let region = core
+        .maps()
+        .iter()
+        .find(|r| (r.from..r.to).contains(&ip))
+        .unwrap();
+    assert!(region.prot.execute());
+    assert!(core.read::<u8>(ip).is_ok());
+
+    let sp = thread.stack_pointer().unwrap() as usize;
+    assert!(core.read_pointer(sp).is_ok());
+}
+
+#[test]
+fn test_parse_invalid() {
+    assert!(matches!(
+        CoreDump::parse(b"not an elf".to_vec()),
+        Err(MfError::InvalidElf)
+    ));
+
+    // Executable isn't a core dump.
+    let exe = std::fs::read(std::env::current_exe().unwrap()).unwrap();
+    assert!(matches!(CoreDump::parse(exe), Err(MfError::InvalidElf)));
+}
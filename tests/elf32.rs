@@ -0,0 +1,104 @@
+#![cfg(all(target_os = "linux", target_arch = "x86_64", feature = "external"))]
+
+use memflex::external::{find_process_by_id, ElfClass, RemotePtr, RemotePtr32, RemoteStruct};
+use std::process::Command;
+
+const BASE: u32 = 0x0804_8000;
+/// ELF header + single program header.
+const HEADERS: u32 = 52 + 32;
+
+/// Node of the pointer chain, as laid out in the 32-bit process.
+#[repr(C)]
+#[derive(Clone, Copy)]
+struct Node {
+    next: RemotePtr32<Node>,
+    _pad: u32,
+    value: RemotePtr32<u32>,
+}
+unsafe impl RemoteStruct for Node {}
+
+/// Static i386 executable that loops forever, followed by a chain of 4-byte pointers.
+/// Returns the file, the address of the first pointer and the address of the value.
+fn elf32() -> (Vec<u8>, u32, u32) {
+    let code = [0xEB, 0xFE]; // jmp $
+    let first = BASE + HEADERS + code.len() as u32;
+    let second = first + 4;
+    let value = second + 12;
+
+    let mut data = vec![];
+    data.extend(second.to_le_bytes());
+    data.extend([0; 8]);
+    data.extend(value.to_le_bytes());
+    data.extend(0xCAFE_BABE_u32.to_le_bytes());
+
+    let size = HEADERS + (code.len() + data.len()) as u32;
+
+    let mut elf = b"\x7FELF\x01\x01\x01".to_vec();
+    elf.resize(16, 0);
+    for half in [2u16, 3] {
+        elf.extend(half.to_le_bytes());
+    }
+    for word in [1, BASE + HEADERS, 52, 0, 0] {
+        elf.extend(word.to_le_bytes());
+    }
+    for half in [52u16, 32, 1, 0, 0, 0] {
+        elf.extend(half.to_le_bytes());
+    }
+    for word in [1, 0, BASE, BASE, size, size, 7, 0x1000] {
+        elf.extend(word.to_le_bytes());
+    }
+    elf.extend(code);
+    elf.extend(data);
+
+    (elf, first, value)
+}
+
+#[test]
+fn test_32bit_process() {
+    use std::os::unix::fs::PermissionsExt;
+
+    let (elf, first, value_address) = elf32();
+    let path = std::env::temp_dir().join(format!("memflex-elf32-{}", std::process::id()));
+    std::fs::write(&path, elf).unwrap();
+    std::fs::set_permissions(&path, std::fs::Permissions::from_mode(0o755)).unwrap();
+
+    let Ok(mut child) = Command::new(&path).spawn() else {
+        // Kernel without 32-bit emulation.
+        std::fs::remove_file(&path).unwrap();
+        return;
+    };
+    std::thread::sleep(std::time::Duration::from_millis(50));
+
+    let result = std::panic::catch_unwind(|| {
+        let p = find_process_by_id(child.id()).unwrap();
+        assert_eq!(p.elf_class().unwrap(), ElfClass::Elf32);
+        assert_eq!(p.pointer_size().unwrap(), 4);
+
+        let value = p.resolve_multilevel(first as usize, &[0, 8, 0]).unwrap();
+        assert_eq!(p.read::<u32>(value).unwrap(), 0xCAFE_BABE);
+
+        // Same chain walked through a struct with 4-byte pointers.
+        let node = RemotePtr::<Node>::new(first as usize).bind(&p);
+        let next = node.field::<RemotePtr32<Node>>(0).follow().unwrap();
+        assert_eq!(next.address(), first as usize + 4);
+        let value = next.read().unwrap().value;
+        assert_eq!(value.address(), value_address as usize);
+        assert_eq!(value.widen().bind(&p).read().unwrap(), 0xCAFE_BABE);
+
+        let module = p
+            .find_module(path.file_name().unwrap().to_str().unwrap())
+            .unwrap();
+        assert_eq!(module.base as usize, BASE as usize);
+    });
+
+    child.kill().unwrap();
+    child.wait().unwrap();
+    std::fs::remove_file(&path).unwrap();
+    result.unwrap();
+}
+
+#[test]
+fn test_native_pointer_size() {
+    let p = find_process_by_id(std::process::id()).unwrap();
+    assert_eq!(p.pointer_size().unwrap(), 8);
+}
@@ -0,0 +1,74 @@
+#![cfg(all(target_os = "linux", feature = "external"))]
+
+use memflex::external::{find_process_by_id, Freezer, Location};
+use std::{
+    sync::atomic::{AtomicU32, Ordering},
+    thread::sleep,
+    time::Duration,
+};
+
+fn wait_for(value: &AtomicU32, expected: u32) -> bool {
+    (0..200).any(|_| {
+        sleep(Duration::from_millis(5));
+        value.load(Ordering::SeqCst) == expected
+    })
+}
+
+#[test]
+fn test_freeze() {
+    let value = Box::new(AtomicU32::new(0));
+    let address = value.as_ptr() as usize;
+
+    let p = find_process_by_id(std::process::id()).unwrap();
+    let freezer = Freezer::new(p, Duration::from_millis(1));
+
+    let frozen = freezer.freeze(address, 10_u32);
+    assert!(wait_for(&value, 10));
+
+    value.store(0, Ordering::SeqCst);
+    assert!(wait_for(&value, 10));
+
+    frozen.set(20);
+    assert!(wait_for(&value, 20));
+
+    frozen.pause();
+    assert!(frozen.is_paused());
+    sleep(Duration::from_millis(20));
+    value.store(1, Ordering::SeqCst);
+    sleep(Duration::from_millis(20));
+    assert_eq!(value.load(Ordering::SeqCst), 1);
+
+    frozen.resume();
+    assert!(wait_for(&value, 20));
+
+    drop(frozen);
+    sleep(Duration::from_millis(20));
+    value.store(2, Ordering::SeqCst);
+    sleep(Duration::from_millis(20));
+    assert_eq!(value.load(Ordering::SeqCst), 2);
+    assert!(freezer.is_running());
+}
+
+#[test]
+fn test_freeze_multilevel() {
+    let first = Box::new(AtomicU32::new(0));
+    let second = Box::new(AtomicU32::new(0));
+    let pointer = Box::new(first.as_ptr() as usize);
+    let base = &*pointer as *const usize as usize;
+
+    let p = find_process_by_id(std::process::id()).unwrap();
+    let freezer = Freezer::new(p, Duration::from_millis(1));
+
+    let _frozen = freezer.freeze(
+        Location::Multilevel {
+            base,
+            offsets: vec![0, 0],
+        },
+        5_u32,
+    );
+    assert!(wait_for(&first, 5));
+
+    // Object got reallocated.
+    unsafe { (base as *mut usize).write_volatile(second.as_ptr() as usize) };
+    assert!(wait_for(&second, 5));
+}
@@ -0,0 +1,131 @@
+#![cfg(all(
+    target_arch = "x86_64",
+    any(windows, target_os = "linux"),
+    feature = "internal"
+))]
+
+use memflex::{
+    internal::{allocate, free, protect, protection, InlineHook},
+    types::Protection,
+    MfError,
+};
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+type Unary = extern "sysv64" fn(i32) -> i32;
+
+/// Trampoline of the hook that is being tested, one test at a time uses it.
+static ORIGINAL: AtomicUsize = AtomicUsize::new(0);
+static LOCK: std::sync::Mutex<()> = std::sync::Mutex::new(());
+
+extern "sysv64" fn detour(x: i32) -> i32 {
+    let original: Unary = unsafe { core::mem::transmute(ORIGINAL.load(Ordering::SeqCst)) };
+    original(x) * 100
+}
+
+#[inline(never)]
+extern "sysv64" fn square(x: i32) -> i32 {
+    std::hint::black_box(x) * x
+}
+
+/// Copies the code into executable memory, data goes at offset `0x100`.
+fn function(code: &[u8], data: u32) -> usize {
+    let page = allocate(None, 0x1000, Protection::RW).unwrap() as usize;
+    unsafe {
+        core::ptr::copy_nonoverlapping(code.as_ptr(), page as *mut u8, code.len());
+        ((page + 0x100) as *mut u32).write(data);
+    }
+    protect(page, 0x1000, Protection::RX).unwrap();
+    page
+}
+
+fn call(address: usize, x: i32) -> i32 {
+    let f: Unary = unsafe { core::mem::transmute(address) };
+    std::hint::black_box(f)(x)
+}
+
+fn hook(address: usize) -> memflex::Result<InlineHook<Unary>> {
+    let hook = unsafe { InlineHook::<Unary>::new(address as _, detour)? };
+    ORIGINAL.store(hook.trampoline(), Ordering::SeqCst);
+    Ok(hook)
+}
+
+#[test]
+fn test_inline_hook() {
+    let _lock = LOCK.lock().unwrap();
+    let f = std::hint::black_box(square as Unary);
+    assert_eq!(f(3), 9);
+
+    let hook = hook(f as usize).unwrap();
+    assert_eq!(hook.target(), f as usize);
+    assert_eq!(f(3), 900);
+    assert_eq!((*hook)(4), 16);
+
+    drop(hook);
+    assert_eq!(f(3), 9);
+}
+
+#[test]
+fn test_inline_hook_relocation() {
+    let _lock = LOCK.lock().unwrap();
+
+    // test edi, edi; jz +6; mov eax, 1; ret; mov eax, 2; ret
+    let branch = function(
+        b"\x85\xFF\x74\x06\xB8\x01\x00\x00\x00\xC3\xB8\x02\x00\x00\x00\xC3",
+        0,
+    );
+    // mov eax, [rip + 0xFA]; add eax, edi; ret
+    let relative = function(b"\x8B\x05\xFA\x00\x00\x00\x01\xF8\xC3", 40);
+
+    let h = hook(branch).unwrap();
+    assert_eq!((call(branch, 0), call(branch, 1)), (200, 100));
+    drop(h);
+    assert_eq!((call(branch, 0), call(branch, 1)), (2, 1));
+
+    let h = hook(relative).unwrap();
+    assert_eq!(call(relative, 2), 4200);
+    drop(h);
+    assert_eq!(call(relative, 2), 42);
+
+    free(branch, 0x1000).unwrap();
+    free(relative, 0x1000).unwrap();
+}
+
+#[test]
+fn test_inline_hook_invalid() {
+    let _lock = LOCK.lock().unwrap();
+
+    // jmp +2; ud2; mov eax, 7; ret, the jump lands inside the patch.
+    let inner = function(b"\xEB\x02\x0F\x0B\xB8\x07\x00\x00\x00\xC3", 0);
+    // loop -2
+    let looping = function(b"\xE2\xFE\x90\x90\x90\x90\xC3", 0);
+
+    for f in [inner, looping] {
+        assert!(matches!(hook(f), Err(MfError::InvalidInstruction)));
+    }
+    assert_eq!(call(inner, 0), 7);
+
+    free(inner, 0x1000).unwrap();
+    free(looping, 0x1000).unwrap();
+}
+
+#[test]
+fn test_inline_hook_end_of_mapping() {
+    let _lock = LOCK.lock().unwrap();
+
+    // mov eax, edi; imul eax, edi; ret, right before an unmapped page.
+    let code = b"\x89\xF8\x0F\xAF\xC7\xC3";
+    let page = allocate(None, 0x2000, Protection::RW).unwrap() as usize;
+    free(page + 0x1000, 0x1000).unwrap();
+    let f = page + 0x1000 - code.len();
+    unsafe { core::ptr::copy_nonoverlapping(code.as_ptr(), f as *mut u8, code.len()) };
+    protect(page, 0x1000, Protection::RWX).unwrap();
+
+    let h = hook(f).unwrap();
+    assert_eq!(protection(f).unwrap(), Protection::RWX);
+    assert_eq!(call(f, 3), 900);
+    drop(h);
+    assert_eq!(protection(f).unwrap(), Protection::RWX);
+    assert_eq!(call(f, 3), 9);
+
+    free(page, 0x1000).unwrap();
+}
@@ -44,6 +44,7 @@ mod game {
     }
 }
 
+#[allow(dead_code)]
 struct CFoo([u8; 0x10]);
 
 memflex::interface! {
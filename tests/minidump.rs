@@ -0,0 +1,212 @@
+use memflex::{ida_pat, types::Protection, MemorySource, MfError, Minidump};
+
+const GAME: u64 = 0x1_4000_0000;
+const HEAP: u64 = 0x2000_0000;
+const STACK: u64 = 0x7FF0_0000;
+
+/// Builds an x64 minidump with every stream the reader understands.
+fn minidump() -> Vec<u8> {
+    let mut streams: Vec<(u32, Vec<u8>)> = vec![];
+    // Stream contents are placed after the header and directory, in this order.
+    let base = 32 + 12 * 6;
+
+    let mut sysinfo = 9u16.to_le_bytes().to_vec();
+    sysinfo.resize(56, 0);
+    streams.push((7, sysinfo));
+
+    // `.text` of the module: signature followed by a pointer to the heap.
+    let mut text = b"\x90\x48\x8B\x05\x11\x22\x33\x44\xC3".to_vec();
+    text.resize(0x10, 0);
+    text.extend((HEAP + 8).to_le_bytes());
+    let mut heap = vec![0; 8];
+    heap.extend((HEAP + 0x10).to_le_bytes());
+    heap.extend(1337u32.to_le_bytes());
+    let stack = vec![0xAA; 0x20];
+
+    let mut context = vec![0; 1232];
+    context[152..160].copy_from_slice(&(STACK + 0x10).to_le_bytes());
+    context[248..256].copy_from_slice(&(GAME + 0x1001).to_le_bytes());
+
+    let name = "C:\\Games\\Game.exe"
+        .encode_utf16()
+        .flat_map(u16::to_le_bytes)
+        .collect::<Vec<_>>();
+
+    // Sizes of streams that go before the blobs.
+    let modules_len = 4 + 108;
+    let memory_len = 4 + 16;
+    let memory64_len = 16 + 16 * 2;
+    let info_len = 16 + 48 * 2;
+    let threads_len = 4 + 48;
+    let blobs = base + 56 + modules_len + memory_len + memory64_len + info_len + threads_len;
+
+    let name_rva = blobs;
+    let context_rva = name_rva + 4 + name.len();
+    let stack_rva = context_rva + context.len();
+    let memory64_rva = stack_rva + stack.len();
+
+    let mut modules = 1u32.to_le_bytes().to_vec();
+    modules.extend(GAME.to_le_bytes());
+    modules.extend(0x2000u32.to_le_bytes());
+    modules.extend([0; 8]);
+    modules.extend((name_rva as u32).to_le_bytes());
+    modules.resize(modules_len, 0);
+    streams.push((4, modules));
+
+    let mut memory = 1u32.to_le_bytes().to_vec();
+    memory.extend(STACK.to_le_bytes());
+    memory.extend((stack.len() as u32).to_le_bytes());
+    memory.extend((stack_rva as u32).to_le_bytes());
+    streams.push((5, memory));
+
+    let mut memory64 = 2u64.to_le_bytes().to_vec();
+    memory64.extend((memory64_rva as u64).to_le_bytes());
+    for (address, data) in [(GAME + 0x1000, &text), (HEAP, &heap)] {
+        memory64.extend(address.to_le_bytes());
+        memory64.extend((data.len() as u64).to_le_bytes());
+    }
+    streams.push((9, memory64));
+
+    let mut info = 16u32.to_le_bytes().to_vec();
+    info.extend(48u32.to_le_bytes());
+    info.extend(2u64.to_le_bytes());
+    for (address, size, protect) in [(GAME + 0x1000, 0x1000u64, 0x20u32), (HEAP, 0x1000, 0x04)] {
+        let mut entry = vec![0; 48];
+        entry[0..8].copy_from_slice(&address.to_le_bytes());
+        entry[24..32].copy_from_slice(&size.to_le_bytes());
+        entry[36..40].copy_from_slice(&protect.to_le_bytes());
+        info.extend(entry);
+    }
+    streams.push((16, info));
+
+    let mut threads = 1u32.to_le_bytes().to_vec();
+    threads.extend(0x1234u32.to_le_bytes());
+    threads.extend([0; 12]);
+    threads.extend(0x7FFD_0000u64.to_le_bytes());
+    threads.extend(STACK.to_le_bytes());
+    threads.extend((stack.len() as u32).to_le_bytes());
+    threads.extend((stack_rva as u32).to_le_bytes());
+    threads.extend((context.len() as u32).to_le_bytes());
+    threads.extend((context_rva as u32).to_le_bytes());
+    streams.push((3, threads));
+
+    let mut dump = b"MDMP".to_vec();
+    dump.extend(0xA793u32.to_le_bytes());
+    dump.extend((streams.len() as u32).to_le_bytes());
+    dump.extend(32u32.to_le_bytes());
+    dump.resize(32, 0);
+
+    let mut rva = base;
+    for (kind, data) in &streams {
+        dump.extend(kind.to_le_bytes());
+        dump.extend((data.len() as u32).to_le_bytes());
+        dump.extend((rva as u32).to_le_bytes());
+        rva += data.len();
+    }
+    for (_, data) in &streams {
+        dump.extend(data);
+    }
+    assert_eq!(dump.len(), blobs);
+
+    dump.extend((name.len() as u32).to_le_bytes());
+    dump.extend(name);
+    dump.extend(context);
+    dump.extend(stack);
+    dump.extend(text);
+    dump.extend(heap);
+    dump
+}
+
+#[test]
+fn test_minidump_memory() {
+    let dump = Minidump::parse(minidump()).unwrap();
+    assert_eq!(dump.pointer_size().unwrap(), 8);
+
+    let regions = dump
+        .regions()
+        .iter()
+        .map(|r| (r.from as u64, r.to as u64, r.prot))
+        .collect::<Vec<_>>();
+    assert_eq!(
+        regions,
+        [
+            (HEAP, HEAP + 0x14, Some(Protection::RW)),
+            (STACK, STACK + 0x20, None),
+            (GAME + 0x1000, GAME + 0x1018, Some(Protection::RX)),
+        ]
+    );
+
+    let hit = dump
+        .find_pattern_in_module(ida_pat!("48 8B 05 ? ? ? ? C3"), "game.EXE")
+        .unwrap()
+        .collect::<Vec<_>>();
+    assert_eq!(hit, [GAME as usize + 0x1001]);
+
+    let value = dump
+        .resolve_multilevel(GAME as usize + 0x1010, &[0, 0, 0])
+        .unwrap();
+    assert_eq!(value, HEAP as usize + 0x10);
+    assert_eq!(dump.read::<u32>(value).unwrap(), 1337);
+
+    assert!(matches!(
+        dump.read::<u64>(HEAP as usize + 0x10),
+        Err(MfError::InvalidAddress)
+    ));
+    assert!(matches!(
+        dump.find_module("engine.dll"),
+        Err(MfError::ModuleNotFound)
+    ));
+}
+
+#[test]
+fn test_minidump_modules_and_threads() {
+    let dump = Minidump::parse(minidump()).unwrap();
+
+    let module = &dump.modules()[0];
+    assert_eq!(module.name, "Game.exe");
+    assert_eq!(module.path, "C:\\Games\\Game.exe");
+    assert_eq!((module.base as u64, module.size), (GAME, 0x2000));
+
+    let thread = &dump.threads()[0];
+    assert_eq!(thread.id, 0x1234);
+    assert_eq!(thread.teb, 0x7FFD_0000);
+    assert_eq!(thread.stack, STACK as usize..STACK as usize + 0x20);
+    assert_eq!(thread.instruction_pointer(), Some(GAME + 0x1001));
+    assert_eq!(thread.stack_pointer(), Some(STACK + 0x10));
+    assert_eq!(
+        dump.read::<u8>(thread.stack_pointer().unwrap() as usize)
+            .unwrap(),
+        0xAA
+    );
+}
+
+#[test]
+fn test_minidump_invalid() {
+    assert!(matches!(
+        Minidump::parse(b"MDMP".to_vec()),
+        Err(MfError::InvalidMinidump)
+    ));
+    assert!(matches!(
+        Minidump::parse(b"\x7FELF".to_vec()),
+        Err(MfError::InvalidMinidump)
+    ));
+
+    // Memory info entries that are too small or don't fit into the stream.
+    let header = [16, 0, 0, 0, 48, 0, 0, 0, 2, 0, 0, 0, 0, 0, 0, 0];
+    for (offset, value) in [(4, 24u64), (8, 3), (8, u64::MAX / 2)] {
+        let mut data = minidump();
+        let info = data.windows(16).position(|w| w == header).unwrap();
+        let len = if offset == 4 { 4 } else { 8 };
+        data[info + offset..][..len].copy_from_slice(&value.to_le_bytes()[..len]);
+        assert!(
+            matches!(Minidump::parse(data), Err(MfError::InvalidMinidump)),
+            "{offset} {value}"
+        );
+    }
+
+    // Truncated memory is shortened.
+    let mut data = minidump();
+    data.truncate(data.len() - 4);
+    let dump = Minidump::parse(data).unwrap();
+    assert_eq!(dump.regions()[0].to, HEAP as usize + 0x10);
+}
@@ -0,0 +1,173 @@
+#![cfg(all(target_os = "linux", target_arch = "x86_64", feature = "external"))]
+
+use memflex::external::{find_process_by_id, ProcessIterator};
+use std::{
+    ffi::CString,
+    os::unix::{ffi::OsStrExt, process::CommandExt},
+    process::Command,
+    ptr::null,
+    time::{Duration, Instant},
+};
+
+const BASE: u64 = 0x40_0000;
+
+/// Static x86-64 executable that loops forever.
+fn elf64() -> Vec<u8> {
+    let code = [0xEB, 0xFE]; // jmp $
+    let headers = 64 + 56;
+    let size = headers + code.len() as u64;
+
+    let mut elf = b"\x7FELF\x02\x01\x01".to_vec();
+    elf.resize(16, 0);
+    for half in [2u16, 62] {
+        elf.extend(half.to_le_bytes());
+    }
+    elf.extend(1u32.to_le_bytes());
+    for word in [BASE + headers, 64, 0] {
+        elf.extend(word.to_le_bytes());
+    }
+    elf.extend(0u32.to_le_bytes());
+    for half in [64u16, 56, 1, 0, 0, 0] {
+        elf.extend(half.to_le_bytes());
+    }
+    elf.extend(1u32.to_le_bytes());
+    elf.extend(5u32.to_le_bytes());
+    for word in [0, BASE, BASE, size, size, 0x1000] {
+        elf.extend(word.to_le_bytes());
+    }
+    elf.extend(code);
+    elf
+}
+
+#[test]
+fn test_mount_namespace_paths() {
+    // The executable only exists on a tmpfs mounted in the child's mount namespace.
+    let dir = std::env::temp_dir().join(format!("memflex-mnt-{}", std::process::id()));
+    std::fs::create_dir_all(&dir).unwrap();
+    let exe = dir.join("loop");
+
+    let elf = elf64();
+    let c_dir = CString::new(dir.as_os_str().as_bytes()).unwrap();
+    let c_exe = CString::new(exe.as_os_str().as_bytes()).unwrap();
+    let data = elf.clone();
+
+    let child = unsafe {
+        Command::new(&exe)
+            .pre_exec(move || {
+                let ok = libc::unshare(libc::CLONE_NEWNS) == 0
+                    && libc::mount(
+                        null(),
+                        c"/".as_ptr(),
+                        null(),
+                        libc::MS_REC | libc::MS_PRIVATE,
+                        null(),
+                    ) == 0
+                    && libc::mount(
+                        c"none".as_ptr(),
+                        c_dir.as_ptr(),
+                        c"tmpfs".as_ptr(),
+                        0,
+                        null(),
+                    ) == 0;
+                let fd = libc::open(c_exe.as_ptr(), libc::O_CREAT | libc::O_WRONLY, 0o755);
+                if !ok
+                    || fd < 0
+                    || libc::write(fd, data.as_ptr().cast(), data.len()) != data.len() as isize
+                    || libc::close(fd) != 0
+                {
+                    return Err(std::io::Error::last_os_error());
+                }
+                Ok(())
+            })
+            .spawn()
+    };
+    let mut child = match child {
+        Ok(child) => child,
+        // Not allowed to create namespaces, e.g. in an unprivileged container.
+        Err(e) if e.raw_os_error() == Some(libc::EPERM) => {
+            std::fs::remove_dir_all(&dir).unwrap();
+            return;
+        }
+        Err(e) => panic!("{e}"),
+    };
+
+    let result = std::panic::catch_unwind(|| {
+        let p = find_process_by_id(child.id()).unwrap();
+        assert!(std::fs::metadata(&exe).is_err());
+        assert_eq!(p.path().unwrap(), exe.to_str().unwrap());
+
+        let module = p.find_module("loop").unwrap();
+        assert_eq!(module.base as u64, BASE);
+
+        let path = p.module_path("LOOP").unwrap();
+        assert_eq!(path, p.resolve_path(&exe));
+        assert_eq!(std::fs::read(&path).unwrap(), elf);
+        assert_eq!(
+            std::fs::read(p.resolve_path(p.path().unwrap())).unwrap(),
+            elf
+        );
+    });
+
+    child.kill().unwrap();
+    child.wait().unwrap();
+    std::fs::remove_dir_all(&dir).unwrap();
+    result.unwrap();
+}
+
+#[test]
+fn test_namespace_ids() {
+    let this = find_process_by_id(std::process::id()).unwrap();
+    assert_eq!(this.namespace_ids().unwrap()[0], std::process::id());
+
+    // Children of the shell live in a new PID namespace.
+    let shell = unsafe {
+        Command::new("/bin/sh")
+            .args(["-c", "sleep 30 & wait"])
+            .pre_exec(|| {
+                if libc::unshare(libc::CLONE_NEWPID) != 0 {
+                    return Err(std::io::Error::last_os_error());
+                }
+                Ok(())
+            })
+            .spawn()
+    };
+    let mut shell = match shell {
+        Ok(shell) => shell,
+        // Not allowed to create namespaces, e.g. in an unprivileged container.
+        Err(e) if e.raw_os_error() == Some(libc::EPERM) => return,
+        Err(e) => panic!("{e}"),
+    };
+    let shell_process = find_process_by_id(shell.id()).unwrap();
+
+    let start = Instant::now();
+    let sleep = loop {
+        // The forked shell is a child as well until it execs `sleep`.
+        let children = shell_process.children().unwrap();
+        if let Some(sleep) = children.into_iter().find(|c| c.name == "sleep") {
+            break sleep;
+        }
+        assert!(start.elapsed() < Duration::from_secs(5));
+        std::thread::sleep(Duration::from_millis(5));
+    };
+
+    let result = std::panic::catch_unwind(|| {
+        assert_eq!(sleep.namespace_ids, [sleep.id, 1]);
+        assert_eq!(
+            sleep.open().unwrap().namespace_ids().unwrap(),
+            [sleep.id, 1]
+        );
+        assert_eq!(shell_process.namespace_ids().unwrap(), [shell.id()]);
+
+        let entry = ProcessIterator::new()
+            .unwrap()
+            .flatten()
+            .find(|e| e.id == shell.id())
+            .unwrap();
+        assert_eq!(entry.namespace_ids, [shell.id()]);
+    });
+
+    unsafe { libc::kill(sleep.id as _, libc::SIGKILL) };
+    shell.kill().unwrap();
+    shell.wait().unwrap();
+    result.unwrap();
+}
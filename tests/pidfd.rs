@@ -0,0 +1,85 @@
+#![cfg(all(target_os = "linux", feature = "external"))]
+
+use memflex::{
+    external::{find_process_by_id, OwnedProcess},
+    MfError,
+};
+use std::{
+    process::Command,
+    time::{Duration, Instant},
+};
+
+#[test]
+fn test_wait_exit() {
+    let mut child = Command::new("sleep").arg("0.2").spawn().unwrap();
+
+    let p = find_process_by_id(child.id()).unwrap();
+    assert!(p.pidfd().is_some());
+    assert!(p.is_alive().unwrap());
+    assert!(!p.wait_exit(Some(Duration::from_millis(10))).unwrap());
+
+    // Sub-millisecond timeouts still wait.
+    let start = Instant::now();
+    assert!(!p.wait_exit(Some(Duration::from_micros(500))).unwrap());
+    assert!(start.elapsed() >= Duration::from_micros(500));
+
+    assert!(p.wait_exit(None).unwrap());
+    assert!(!p.is_alive().unwrap());
+    assert!(matches!(p.read::<u8>(0x1000), Err(MfError::ProcessDied)));
+    assert!(matches!(p.maps(), Err(MfError::ProcessDied)));
+    assert!(matches!(p.write(0x1000, &0_u8), Err(MfError::ProcessDied)));
+
+    child.wait().unwrap();
+}
+
+#[test]
+fn test_wait_exit_without_pidfd() {
+    let mut child = Command::new("sleep").arg("0.2").spawn().unwrap();
+
+    let p = OwnedProcess::from_pid(child.id());
+    assert!(p.pidfd().is_none());
+    assert!(p.is_alive().unwrap());
+    assert!(p.wait_exit(Some(Duration::from_secs(5))).unwrap());
+
+    child.wait().unwrap();
+    assert!(matches!(p.maps(), Err(MfError::ProcessDied)));
+}
+
+#[test]
+fn test_wait_exit_interrupted() {
+    extern "C" fn handler(_: libc::c_int) {}
+
+    let mut child = Command::new("sleep").arg("5").spawn().unwrap();
+    let p = find_process_by_id(child.id()).unwrap();
+
+    // Without `SA_RESTART` every signal interrupts `poll`.
+    unsafe {
+        let mut action: libc::sigaction = std::mem::zeroed();
+        action.sa_sigaction = handler as extern "C" fn(libc::c_int) as libc::sighandler_t;
+        assert_eq!(
+            libc::sigaction(libc::SIGUSR1, &action, std::ptr::null_mut()),
+            0
+        );
+    }
+    let thread = unsafe { libc::pthread_self() };
+    let done = std::sync::Arc::new(std::sync::atomic::AtomicBool::new(false));
+    let signals = std::thread::spawn({
+        let done = done.clone();
+        move || {
+            while !done.load(std::sync::atomic::Ordering::SeqCst) {
+                unsafe { libc::pthread_kill(thread, libc::SIGUSR1) };
+                std::thread::sleep(Duration::from_millis(5));
+            }
+        }
+    });
+
+    let start = Instant::now();
+    assert!(!p.wait_exit(Some(Duration::from_millis(100))).unwrap());
+    let elapsed = start.elapsed();
+    done.store(true, std::sync::atomic::Ordering::SeqCst);
+    signals.join().unwrap();
+
+    child.kill().unwrap();
+    child.wait().unwrap();
+    assert!(elapsed < Duration::from_secs(1), "{elapsed:?}");
+}
@@ -0,0 +1,64 @@
+#![cfg(all(target_os = "linux", feature = "external"))]
+
+use memflex::external::{find_process_by_id, PointerChain, PointerScanner};
+use std::sync::atomic::{AtomicPtr, Ordering};
+
+#[repr(C)]
+struct Outer {
+    _pad: [u64; 3],
+    inner: Box<Inner>,
+}
+
+#[repr(C)]
+struct Inner {
+    _pad: [u64; 5],
+    value: u64,
+}
+
+static ROOT: AtomicPtr<Outer> = AtomicPtr::new(core::ptr::null_mut());
+
+#[test]
+fn test_pointer_scan() {
+    let outer = Box::new(Outer {
+        _pad: [0; 3],
+        inner: Box::new(Inner {
+            _pad: [0; 5],
+            value: 0xDEAD_BEEF,
+        }),
+    });
+    ROOT.store(Box::into_raw(outer), Ordering::SeqCst);
+
+    let p = find_process_by_id(std::process::id()).unwrap();
+    let target = unsafe { &(*ROOT.load(Ordering::SeqCst)).inner.value as *const u64 as usize };
+
+    let module = p
+        .modules()
+        .unwrap()
+        .find(|m| {
+            let base = m.base as usize;
+            (base..base + m.size).contains(&(&ROOT as *const _ as usize))
+        })
+        .unwrap();
+
+    let expected = PointerChain {
+        module: module.name.clone(),
+        base_offset: &ROOT as *const _ as usize - module.base as usize,
+        offsets: vec![24, 40],
+    };
+    assert_eq!(expected.resolve(&p).unwrap(), target);
+
+    let mut chains = PointerScanner::new(&p)
+        .max_depth(3)
+        .max_offset(0x100)
+        .scan(target)
+        .unwrap();
+    assert!(chains.contains(&expected));
+
+    PointerChain::retain_valid(&mut chains, &p, target).unwrap();
+    assert!(chains.contains(&expected));
+
+    PointerChain::retain_valid(&mut chains, &p, target + 8).unwrap();
+    assert!(chains.is_empty());
+
+    drop(unsafe { Box::from_raw(ROOT.swap(core::ptr::null_mut(), Ordering::SeqCst)) });
+}
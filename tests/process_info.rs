@@ -0,0 +1,118 @@
+#![cfg(all(target_os = "linux", feature = "external"))]
+
+use memflex::external::{find_process_by_id, ElfClass, ProcessState};
+use std::{
+    os::unix::fs::MetadataExt,
+    thread::sleep,
+    time::{Duration, Instant},
+};
+
+#[test]
+fn test_process_info() {
+    let p = find_process_by_id(std::process::id()).unwrap();
+
+    assert_eq!(p.cmdline().unwrap()[0], std::env::args().next().unwrap());
+    assert_eq!(
+        p.cwd().unwrap(),
+        std::env::current_dir().unwrap().to_string_lossy()
+    );
+    assert!(p
+        .environ()
+        .unwrap()
+        .iter()
+        .any(|(k, _)| std::env::var_os(k).is_some()));
+    assert!(matches!(
+        p.state().unwrap(),
+        ProcessState::Running | ProcessState::Sleeping
+    ));
+    assert!(p.thread_count().unwrap() >= 1);
+    assert!(p.start_time().unwrap() <= std::time::SystemTime::now());
+    assert_eq!(p.elf_class().unwrap(), ElfClass::Elf64);
+
+    let meta = std::fs::metadata("/proc/self").unwrap();
+    assert_eq!(p.uid().unwrap(), meta.uid());
+    assert_eq!(p.gid().unwrap(), meta.gid());
+}
+
+#[test]
+fn test_process_iterator() {
+    use memflex::external::{OwnedProcess, ProcessIterator, ProcessState};
+    use std::{process::Command, time::Duration};
+
+    // Zombie has no `exe` link but still has to be listed.
+    let mut zombie = Command::new("/bin/true").spawn().unwrap();
+    let p = OwnedProcess::from_pid(zombie.id());
+    while p.state().unwrap() != ProcessState::Zombie {
+        std::thread::sleep(Duration::from_millis(5));
+    }
+
+    let entries = ProcessIterator::new()
+        .unwrap()
+        .collect::<memflex::Result<Vec<_>>>()
+        .unwrap();
+    zombie.wait().unwrap();
+
+    let this = entries.iter().find(|e| e.id == std::process::id()).unwrap();
+    let exe = std::env::current_exe().unwrap();
+    assert_eq!(this.path.as_deref(), exe.to_str());
+    assert_eq!(this.name, exe.file_name().unwrap().to_str().unwrap());
+
+    let zombie = entries.iter().find(|e| e.id == zombie.id()).unwrap();
+    assert_eq!(zombie.path, None);
+    assert_eq!(zombie.name, "true");
+    assert_eq!(zombie.parent_id, std::process::id());
+}
+
+#[test]
+fn test_cmdline_empty_args() {
+    let mut child = std::process::Command::new("sh")
+        .args(["-c", "sleep 5; true", "sh", "", "x"])
+        .spawn()
+        .unwrap();
+    let p = find_process_by_id(child.id()).unwrap();
+
+    // The command line is empty until the child executes `sh`.
+    let start = Instant::now();
+    let cmdline = loop {
+        let cmdline = p.cmdline();
+        if !cmdline.as_ref().is_ok_and(Vec::is_empty) || start.elapsed() > Duration::from_secs(5) {
+            break cmdline;
+        }
+        sleep(Duration::from_millis(10));
+    };
+    _ = child.kill();
+    _ = child.wait();
+    assert_eq!(
+        cmdline.unwrap(),
+        ["sh", "-c", "sleep 5; true", "sh", "", "x"]
+    );
+}
+
+#[test]
+fn test_module_name_with_spaces() {
+    let name = format!("memflex module {}.bin", std::process::id());
+    let path = std::env::temp_dir().join(&name);
+    std::fs::write(&path, [0_u8; 0x2000]).unwrap();
+
+    let file = std::fs::File::open(&path).unwrap();
+    let map = unsafe {
+        libc::mmap(
+            core::ptr::null_mut(),
+            0x2000,
+            libc::PROT_READ,
+            libc::MAP_PRIVATE,
+            std::os::fd::AsRawFd::as_raw_fd(&file),
+            0,
+        )
+    };
+    assert_ne!(map, libc::MAP_FAILED);
+
+    let p = find_process_by_id(std::process::id()).unwrap();
+    let module = p.find_module(&name);
+    unsafe { libc::munmap(map, 0x2000) };
+    _ = std::fs::remove_file(&path);
+
+    let module = module.unwrap();
+    assert_eq!(module.base as usize, map as usize);
+    assert!(module.size >= 0x2000);
+}
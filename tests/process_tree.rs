@@ -0,0 +1,101 @@
+#![cfg(feature = "external")]
+
+use memflex::external::{ProcessEntry, ProcessTree};
+
+fn entry(id: u32, parent_id: u32, name: &str) -> ProcessEntry {
+    ProcessEntry {
+        id,
+        name: name.to_owned(),
+        parent_id,
+        #[cfg(unix)]
+        path: None,
+        #[cfg(unix)]
+        namespace_ids: vec![id],
+    }
+}
+
+fn ids<'a>(iter: impl Iterator<Item = &'a ProcessEntry>) -> Vec<u32> {
+    iter.map(|e| e.id).collect()
+}
+
+#[test]
+fn test_tree_navigation() {
+    let tree = ProcessTree::from_entries([
+        entry(0, 0, "idle"),
+        entry(1, 0, "init"),
+        entry(10, 1, "launcher"),
+        entry(11, 10, "updater"),
+        entry(12, 10, "sandbox"),
+        entry(13, 12, "game"),
+        entry(20, 99, "orphan"),
+    ]);
+
+    assert_eq!(tree.get(13).unwrap().name, "game");
+    assert_eq!(tree.parent(13).unwrap().id, 12);
+    assert!(tree.parent(0).is_none());
+    assert!(tree.parent(20).is_none());
+
+    assert_eq!(ids(tree.children(10)), [11, 12]);
+    assert_eq!(ids(tree.children(0)), [1]);
+    assert_eq!(ids(tree.descendants(1)), [10, 11, 12, 13]);
+    assert_eq!(ids(tree.ancestors(13)), [12, 10, 1, 0]);
+    assert_eq!(ids(tree.roots()), [0, 20]);
+}
+
+#[test]
+fn test_tree_reused_ids() {
+    // 2 and 3 are each other's parents after their ids were reused.
+    let tree = ProcessTree::from_entries([entry(2, 3, "a"), entry(3, 2, "b")]);
+    assert_eq!(ids(tree.ancestors(2)), [3]);
+    assert_eq!(ids(tree.descendants(2)), [3]);
+}
+
+#[cfg(target_os = "linux")]
+#[test]
+fn test_process_hierarchy() {
+    use memflex::external::find_process_by_id;
+    use std::{
+        process::Command,
+        time::{Duration, Instant},
+    };
+
+    let mut shell = Command::new("/bin/sh")
+        .args(["-c", "sleep 30 & wait"])
+        .spawn()
+        .unwrap();
+    let this = find_process_by_id(std::process::id()).unwrap();
+    let shell_process = find_process_by_id(shell.id()).unwrap();
+
+    let start = Instant::now();
+    // The child is a copy of the shell until it executes `sleep`.
+    let sleep = loop {
+        if let Some(sleep) = shell_process
+            .children()
+            .unwrap()
+            .into_iter()
+            .find(|c| c.name == "sleep")
+        {
+            break sleep;
+        }
+        assert!(start.elapsed() < Duration::from_secs(5));
+        std::thread::sleep(Duration::from_millis(10));
+    };
+
+    let result = std::panic::catch_unwind(|| {
+        assert!(this.children().unwrap().iter().any(|c| c.id == shell.id()));
+
+        let descendants = this.descendants().unwrap();
+        let shell_index = descendants.iter().position(|c| c.id == shell.id()).unwrap();
+        let sleep_index = descendants.iter().position(|c| c.id == sleep.id).unwrap();
+        assert!(shell_index < sleep_index);
+
+        let parent = sleep.open().unwrap().parent().unwrap();
+        assert_eq!(parent.id, shell.id());
+        assert_eq!(shell_process.parent().unwrap().id, std::process::id());
+    });
+
+    unsafe { libc::kill(sleep.id as _, libc::SIGKILL) };
+    shell.kill().unwrap();
+    shell.wait().unwrap();
+    result.unwrap();
+}
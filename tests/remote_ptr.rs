@@ -0,0 +1,119 @@
+#![cfg(all(target_os = "linux", feature = "external"))]
+
+use memflex::{
+    external::{find_process_by_id, RemotePtr, RemoteStruct},
+    MfError,
+};
+use std::mem::size_of;
+
+#[repr(C)]
+#[derive(Debug, Clone, Copy, PartialEq)]
+struct Weapon {
+    ammo: u32,
+    damage: f32,
+}
+unsafe impl RemoteStruct for Weapon {}
+
+#[repr(C)]
+#[derive(Debug, Clone, Copy)]
+struct Player {
+    health: f32,
+    weapons: RemotePtr<Weapon>,
+}
+unsafe impl RemoteStruct for Player {}
+
+static GLOBAL: u64 = 0x1234;
+
+#[test]
+fn test_remote_ptr() {
+    assert_eq!(size_of::<RemotePtr<Player>>(), size_of::<usize>());
+
+    let weapons = Box::new([
+        Weapon {
+            ammo: 10,
+            damage: 1.5,
+        },
+        Weapon {
+            ammo: 20,
+            damage: 2.5,
+        },
+    ]);
+    let player = Box::new(Player {
+        health: 100.,
+        weapons: RemotePtr::new(weapons.as_ptr() as usize),
+    });
+    let root = Box::new(RemotePtr::<Player>::new(&*player as *const Player as usize));
+
+    let p = find_process_by_id(std::process::id()).unwrap();
+    let root = RemotePtr::<RemotePtr<Player>>::new(&*root as *const _ as usize).bind(&p);
+
+    let player_ptr = root.deref().unwrap();
+    assert_eq!(player_ptr.field::<f32>(0).read().unwrap(), 100.);
+
+    let weapons_ptr = player_ptr.read().unwrap().weapons.bind(&p);
+    assert_eq!(weapons_ptr.offset(1).read().unwrap().ammo, 20);
+
+    weapons_ptr
+        .offset(1)
+        .write(&Weapon {
+            ammo: 5,
+            damage: 0.,
+        })
+        .unwrap();
+    assert_eq!(unsafe { std::ptr::read_volatile(&weapons[1].ammo) }, 5);
+
+    player_ptr.field::<f32>(0).write(&50.).unwrap();
+    assert_eq!(unsafe { std::ptr::read_volatile(&player.health) }, 50.);
+
+    assert!(RemotePtr::<u32>::null().bind(&p).is_null());
+    assert_eq!(
+        format!("{:?}", RemotePtr::<u32>::null().bind(&p)),
+        "BoundPtr<u32>(null)"
+    );
+
+    let global = RemotePtr::<u64>::new(&GLOBAL as *const u64 as usize).bind(&p);
+    let debug = format!("{global:?}");
+    let exe = std::env::current_exe().unwrap();
+    assert!(
+        debug.starts_with(&format!(
+            "BoundPtr<u64>({}+0x",
+            exe.file_name().unwrap().to_str().unwrap()
+        )),
+        "{debug}"
+    );
+}
+
+#[test]
+fn test_remote_ptr_short_read() {
+    let page = unsafe {
+        libc::mmap(
+            std::ptr::null_mut(),
+            0x2000,
+            libc::PROT_READ | libc::PROT_WRITE,
+            libc::MAP_PRIVATE | libc::MAP_ANONYMOUS,
+            -1,
+            0,
+        )
+    } as usize;
+    assert_eq!(unsafe { libc::munmap((page + 0x1000) as _, 0x1000) }, 0);
+
+    // Only the first half of the value is mapped.
+    let p = find_process_by_id(std::process::id()).unwrap();
+    let value = RemotePtr::<u64>::new(page + 0x1000 - 4);
+    assert!(matches!(value.read(&p), Err(MfError::InvalidAddress)));
+    assert!(matches!(
+        value.bind(&p).read(),
+        Err(MfError::InvalidAddress)
+    ));
+    assert!(matches!(
+        u64::read_from(&p, value.address()),
+        Err(MfError::InvalidAddress)
+    ));
+    assert!(matches!(
+        p.read::<u64>(value.address()),
+        Err(MfError::InvalidAddress)
+    ));
+    assert_eq!(value.offset(-1).read(&p).unwrap(), 0);
+
+    unsafe { libc::munmap(page as _, 0x1000) };
+}
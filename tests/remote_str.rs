@@ -0,0 +1,88 @@
+#![cfg(all(target_os = "linux", feature = "external"))]
+
+use memflex::{external::find_process_by_id, MfError};
+
+#[test]
+fn test_str_roundtrip() {
+    let p = find_process_by_id(std::process::id()).unwrap();
+    let buf = vec![0xFFu8; 256];
+    let address = buf.as_ptr() as usize;
+
+    assert_eq!(p.write_str(address, "héllo", true).unwrap(), 7);
+    assert_eq!(p.read_str(address).unwrap(), "héllo");
+    assert_eq!(p.read_str_bounded(address, 3).unwrap(), "hé");
+    assert!(matches!(
+        p.read_str_bounded(address, 2),
+        Err(MfError::InvalidString)
+    ));
+    assert_eq!(p.read_str_lossy(address, 2).unwrap(), "h\u{FFFD}");
+    assert_eq!(p.read_str_bytes(address, 100).unwrap(), "héllo".as_bytes());
+
+    assert_eq!(p.write_utf16(address, "wide ✓", true).unwrap(), 14);
+    assert_eq!(p.read_utf16(address, 100).unwrap(), "wide ✓");
+    assert_eq!(p.read_utf16(address, 4).unwrap(), "wide");
+
+    assert_eq!(p.write_wstr(address, "😀 ok", true).unwrap(), 20);
+    assert_eq!(p.read_wstr(address, 100).unwrap(), "😀 ok");
+
+    p.write::<u32>(address, &0xD800).unwrap();
+    assert!(matches!(
+        p.read_wstr(address, 100),
+        Err(MfError::InvalidString)
+    ));
+    assert_eq!(p.read_wstr_lossy(address, 100).unwrap(), "\u{FFFD} ok");
+
+    p.write_str(address, "xxxxx", true).unwrap();
+    assert_eq!(p.write_str(address, "abc", false).unwrap(), 3);
+    assert_eq!(p.read_str(address).unwrap(), "abcxx");
+}
+
+#[test]
+fn test_str_page_boundary() {
+    let p = find_process_by_id(std::process::id()).unwrap();
+
+    unsafe {
+        let page = libc::sysconf(libc::_SC_PAGESIZE) as usize;
+        let map = libc::mmap(
+            core::ptr::null_mut(),
+            page * 2,
+            libc::PROT_READ | libc::PROT_WRITE,
+            libc::MAP_PRIVATE | libc::MAP_ANONYMOUS,
+            -1,
+            0,
+        ) as usize;
+        assert_ne!(map, libc::MAP_FAILED as usize);
+        libc::mprotect((map + page) as _, page, libc::PROT_NONE);
+
+        let end = map + page;
+        core::ptr::copy_nonoverlapping(c"tail".as_ptr().cast::<u8>(), (end - 5) as *mut u8, 5);
+        assert_eq!(p.read_str(end - 5).unwrap(), "tail");
+        ((end - 4) as *mut [u16; 2]).write([0x41, 0]);
+        assert_eq!(p.read_utf16(end - 4, 100).unwrap(), "A");
+
+        core::ptr::copy_nonoverlapping(b"tail!".as_ptr(), (end - 5) as *mut u8, 5);
+        assert_eq!(p.read_str_bounded(end - 5, 5).unwrap(), "tail!");
+        assert!(p.read_str(end - 5).is_err());
+
+        libc::munmap(map as _, page * 2);
+    }
+}
+
+#[test]
+fn test_str_unterminated() {
+    let p = find_process_by_id(std::process::id()).unwrap();
+    let mut buf = vec![b'a'; 0x2000];
+    let address = buf.as_ptr() as usize;
+
+    assert!(matches!(
+        p.read_str(address),
+        Err(MfError::UnterminatedString)
+    ));
+    assert_eq!(p.read_str_bounded(address, 3).unwrap(), "aaa");
+
+    buf[0x1000] = 0;
+    assert_eq!(p.read_str(address).unwrap().len(), 0x1000);
+    buf[0x1000] = b'a';
+    buf[0xFFF] = 0;
+    assert_eq!(p.read_str(address).unwrap().len(), 0xFFF);
+}
@@ -0,0 +1,61 @@
+#![cfg(all(target_os = "linux", feature = "external"))]
+
+use core::mem::offset_of;
+use memflex::external::{find_process_by_id, RemotePtr, RemoteStruct};
+
+memflex::makestruct! {
+    #[derive(Debug, Clone, Copy, PartialEq)]
+    pub struct Entity unsafe impl RemoteStruct {
+        id: u32,
+    }
+
+    #[derive(Debug, Clone, Copy, PartialEq)]
+    pub struct Player unsafe impl RemoteStruct : pub Entity {
+        health: f32,
+        weapon: *mut Weapon,
+        backup: RemotePtr<Weapon>,
+    }
+
+    #[derive(Debug, Clone, Copy, PartialEq)]
+    pub struct Weapon unsafe impl RemoteStruct {
+        ammo: u32,
+    }
+}
+
+#[test]
+fn test_remote_struct() {
+    let mut weapons = Box::new([Weapon { ammo: 30 }, Weapon { ammo: 7 }]);
+    let player = Box::new(Player {
+        parent: Entity { id: 42 },
+        health: 75.,
+        weapon: &mut weapons[0],
+        backup: RemotePtr::from(&weapons[1] as *const Weapon),
+    });
+    let address = &*player as *const Player as usize;
+
+    let p = find_process_by_id(std::process::id()).unwrap();
+    assert_eq!(Player::read_from(&p, address).unwrap(), *player);
+
+    let remote = Player::remote(&p, address);
+    assert_eq!(remote.parent().read().unwrap().id, 42);
+
+    let weapon = remote.weapon().follow().unwrap();
+    assert_eq!(
+        remote.weapon().address(),
+        remote
+            .field::<*mut Weapon>(offset_of!(Player, weapon))
+            .address()
+    );
+    assert_eq!(weapon.read().unwrap().ammo, 30);
+    Weapon { ammo: 1 }.write_to(&p, weapon.address()).unwrap();
+    assert_eq!(unsafe { core::ptr::read_volatile(&weapons[0].ammo) }, 1);
+
+    let backup = remote.backup().follow().unwrap();
+    assert_eq!(remote.health().read().unwrap(), 75.);
+    assert_eq!(backup.read().unwrap().ammo, 7);
+
+    let mut copy = remote.read().unwrap();
+    copy.parent.id = 7;
+    copy.write_to(&p, address).unwrap();
+    assert_eq!(unsafe { core::ptr::read_volatile(&player.id) }, 7);
+}
@@ -0,0 +1,102 @@
+#![cfg(all(target_os = "linux", feature = "external"))]
+
+use memflex::{
+    external::{find_process_by_id, ScanFilter, ScanStorage, Scanner},
+    MfError,
+};
+use std::hint::black_box;
+
+#[test]
+fn test_scan_narrowing() {
+    let p = find_process_by_id(std::process::id()).unwrap();
+    let mut value = Box::new(black_box(
+        0x1122_3344_5566_7788_u64 ^ std::process::id() as u64,
+    ));
+    let address = &*value as *const u64 as usize;
+
+    for storage in [ScanStorage::Memory, ScanStorage::Disk(std::env::temp_dir())] {
+        let mut scanner = Scanner::<u64>::new(&p).storage(storage);
+
+        assert!(scanner.first_exact(*value).unwrap() >= 1);
+        assert!(scanner
+            .results(usize::MAX)
+            .unwrap()
+            .contains(&(address, *value)));
+
+        **black_box(&mut value) += 5;
+        scanner.next(ScanFilter::IncreasedBy(5)).unwrap();
+        scanner.next(ScanFilter::Unchanged).unwrap();
+        assert_eq!(scanner.results(usize::MAX).unwrap(), [(address, *value)]);
+
+        **black_box(&mut value) -= 1;
+        assert_eq!(scanner.next(ScanFilter::Increased).unwrap(), 0);
+        assert!(scanner.is_empty());
+    }
+}
+
+#[test]
+fn test_scan_unknown_float() {
+    let p = find_process_by_id(std::process::id()).unwrap();
+    let mut health = Box::new(black_box([0_f32; 256]));
+    health[128] = 100.;
+    let address = &health[128] as *const f32 as usize;
+
+    let mut scanner = Scanner::<f32>::new(&p)
+        .tolerance(0.01)
+        .regions(|r| r.from <= address && address < r.to);
+
+    assert!(scanner.first_unknown().unwrap() > 256);
+
+    health[128] -= 25.;
+    health[0] = 1.;
+    scanner.next(ScanFilter::Decreased).unwrap();
+    scanner.next(ScanFilter::Exact(75.001)).unwrap();
+    assert!(scanner
+        .results(usize::MAX)
+        .unwrap()
+        .iter()
+        .any(|(a, v)| *a == address && *v == 75.));
+    black_box(&health);
+}
+
+#[test]
+fn test_scan_pattern() {
+    let p = find_process_by_id(std::process::id()).unwrap();
+    let bytes = Box::new(black_box(*b"memflex scanner!"));
+    let address = bytes.as_ptr() as usize;
+
+    let mut scanner = Scanner::<[u8; 16]>::new(&p);
+    scanner
+        .first_matching(memflex::peid_pat!(
+            "6D 65 6D 66 6C 65 78 20 73 63 61 6E 6E 65 72 21"
+        ))
+        .unwrap();
+    assert!(scanner
+        .results(usize::MAX)
+        .unwrap()
+        .iter()
+        .any(|(a, _)| *a == address));
+}
+
+#[test]
+fn test_scan_process_died() {
+    let mut child = std::process::Command::new("sleep")
+        .arg("5")
+        .spawn()
+        .unwrap();
+    let p = find_process_by_id(child.id()).unwrap();
+
+    let mut scanner = Scanner::<u64>::new(&p).regions(|r| r.prot.write());
+    let count = scanner.first_exact(0).unwrap();
+    assert!(count > 0);
+
+    child.kill().unwrap();
+    child.wait().unwrap();
+
+    assert!(matches!(
+        scanner.next(ScanFilter::Unchanged),
+        Err(MfError::ProcessDied)
+    ));
+    assert_eq!(scanner.len(), count);
+    assert!(matches!(scanner.first_exact(0), Err(MfError::ProcessDied)));
+}
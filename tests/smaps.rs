@@ -0,0 +1,62 @@
+#![cfg(all(target_os = "linux", feature = "external"))]
+
+use memflex::external::find_process_by_id;
+
+const MB: usize = 0x10_0000;
+
+#[test]
+fn test_region_stats() {
+    let p = find_process_by_id(std::process::id()).unwrap();
+
+    let map = unsafe {
+        libc::mmap(
+            core::ptr::null_mut(),
+            64 * MB,
+            libc::PROT_READ | libc::PROT_WRITE,
+            libc::MAP_PRIVATE | libc::MAP_ANONYMOUS,
+            -1,
+            0,
+        )
+    };
+    assert_ne!(map, libc::MAP_FAILED);
+    let map = map as usize;
+    // Only first 4 MiB become resident.
+    unsafe { core::ptr::write_bytes(map as *mut u8, 1, 4 * MB) };
+
+    let stats = p.region_stats().unwrap();
+    unsafe { libc::munmap(map as _, 64 * MB) };
+
+    let region = stats
+        .iter()
+        .find(|s| (s.region.from..s.region.to).contains(&map))
+        .unwrap();
+    assert!(region.region.prot.write());
+    assert!(region.path.is_none());
+    assert!(region.usage.rss >= 4 * MB && region.usage.rss < 8 * MB);
+    assert!(region.usage.private_dirty >= 4 * MB);
+    assert_eq!(region.usage.dirty(), region.usage.private_dirty);
+    assert_eq!(region.usage.anonymous, region.usage.rss);
+
+    let total = stats
+        .iter()
+        .map(|s| s.usage)
+        .sum::<memflex::external::MemoryUsage>();
+    assert!(total.rss >= region.usage.rss);
+    assert!(stats.iter().any(|s| s.path.as_deref() == Some("[stack]")));
+}
+
+#[test]
+fn test_module_stats() {
+    let p = find_process_by_id(std::process::id()).unwrap();
+    let exe = std::env::current_exe().unwrap();
+
+    let stats = p.module_stats().unwrap();
+    let this = stats
+        .iter()
+        .find(|m| m.path == exe.to_str().unwrap())
+        .unwrap();
+    assert!(this.usage.rss > 0);
+
+    let module = p.find_module(&this.name).unwrap();
+    assert_eq!((this.base, this.size), (module.base as usize, module.size));
+}
@@ -0,0 +1,114 @@
+use memflex::{Snapshot, SnapshotChange, SnapshotRegion};
+use std::hint::black_box;
+
+#[test]
+fn test_snapshot_diff() {
+    let mut buf = black_box([0u8; 64]);
+    let range = buf.as_ptr() as usize..buf.as_ptr() as usize + buf.len();
+
+    let before = unsafe { Snapshot::capture_local([range.clone()]) };
+    buf[4] = 1;
+    buf[5] = 2;
+    buf[40] = 3;
+    black_box(&mut buf);
+    let after = unsafe { Snapshot::capture_local([range.clone()]) };
+
+    assert_eq!(
+        before.diff(&after),
+        [
+            SnapshotChange {
+                address: range.start + 4,
+                old: vec![0, 0],
+                new: vec![1, 2],
+            },
+            SnapshotChange {
+                address: range.start + 40,
+                old: vec![0],
+                new: vec![3],
+            },
+        ]
+    );
+    assert_eq!(
+        after.get(range.start + 4..range.start + 6),
+        Some(&[1, 2][..])
+    );
+    assert!(after.get(range.start + 60..range.end + 1).is_none());
+}
+
+#[test]
+fn test_snapshot_partial_overlap() {
+    let a = Snapshot::from_regions(vec![
+        SnapshotRegion {
+            address: 0x1000,
+            data: vec![1; 16],
+        },
+        SnapshotRegion {
+            address: 0x2000,
+            data: vec![1; 16],
+        },
+    ]);
+    let b = Snapshot::from_regions(vec![SnapshotRegion {
+        address: 0x1008,
+        data: vec![2; 0x1000],
+    }]);
+
+    let changes = a.diff(&b);
+    assert_eq!(changes.len(), 2);
+    assert_eq!((changes[0].address, changes[0].old.len()), (0x1008, 8));
+    assert_eq!((changes[1].address, changes[1].old.len()), (0x2000, 8));
+}
+
+#[test]
+fn test_snapshot_save_load() {
+    let snapshot = Snapshot::from_regions(vec![SnapshotRegion {
+        address: 0xDEAD_0000,
+        data: (0..=255).collect(),
+    }]);
+
+    let path = std::env::temp_dir().join(format!("memflex-snapshot-{}.bin", std::process::id()));
+    snapshot.save(&path).unwrap();
+    let loaded = Snapshot::load(&path);
+    std::fs::remove_file(&path).unwrap();
+
+    assert_eq!(loaded.unwrap(), snapshot);
+}
+
+#[cfg(all(target_os = "linux", feature = "external"))]
+#[test]
+fn test_snapshot_external() {
+    let p = memflex::external::find_process_by_id(std::process::id()).unwrap();
+    let mut value = Box::new(black_box(0x1234_u32));
+    let address = &*value as *const u32 as usize;
+
+    let before = Snapshot::capture(&p, Some(address..address + 4)).unwrap();
+    **black_box(&mut value) = 0x1235;
+    let after = Snapshot::capture(&p, Some(address..address + 4)).unwrap();
+
+    let changes = before.diff(&after);
+    assert_eq!(changes.len(), 1);
+    assert_eq!(changes[0].address, address);
+    assert_eq!(changes[0].new, [0x35]);
+}
+
+#[cfg(all(target_os = "linux", feature = "external"))]
+#[test]
+fn test_snapshot_external_partial() {
+    let p = memflex::external::find_process_by_id(std::process::id()).unwrap();
+    let map = unsafe {
+        libc::mmap(
+            core::ptr::null_mut(),
+            0x2000,
+            libc::PROT_READ | libc::PROT_WRITE,
+            libc::MAP_PRIVATE | libc::MAP_ANONYMOUS,
+            -1,
+            0,
+        )
+    };
+    assert_ne!(map, libc::MAP_FAILED);
+    let map = map as usize;
+    unsafe { libc::munmap((map + 0x1000) as _, 0x1000) };
+
+    let result = Snapshot::capture(&p, Some(map + 0xF00..map + 0x1100));
+    unsafe { libc::munmap(map as _, 0x1000) };
+    assert!(matches!(result, Err(memflex::MfError::InvalidAddress)));
+}
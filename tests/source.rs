@@ -0,0 +1,177 @@
+use memflex::{
+    ida_pat, LocalMemory, MemoryBuffer, MemorySink, MemorySource, MfError, Snapshot, SnapshotRegion,
+};
+
+/// Generic code that works with any memory.
+fn player_name(memory: &impl MemorySource, base: usize) -> memflex::Result<String> {
+    let name = memory.resolve_multilevel(base, &[0x10, 0x8])?;
+    memory.read_str(name)
+}
+
+fn layout() -> Vec<u8> {
+    let mut data = vec![0u8; 0x40];
+    data[0x10..0x18].copy_from_slice(&0x1020_usize.to_ne_bytes());
+    data[0x28..0x2E].copy_from_slice(b"alice\0");
+    data
+}
+
+#[test]
+fn test_buffer_source() {
+    let mut buffer = MemoryBuffer::new(0x1000, layout());
+    assert_eq!(player_name(&buffer, 0x1000).unwrap(), "alice");
+
+    buffer
+        .write(0x1028, &u32::from_ne_bytes(*b"bob\0"))
+        .unwrap();
+    assert_eq!(player_name(&buffer, 0x1000).unwrap(), "bob");
+
+    assert!(matches!(
+        buffer.read::<u64>(0x103C),
+        Err(MfError::InvalidAddress)
+    ));
+    assert!(matches!(
+        buffer.read::<u8>(0xFFF),
+        Err(MfError::InvalidAddress)
+    ));
+    assert_eq!(
+        buffer
+            .find_pattern(ida_pat!("62 ? 62"), 0x1000, 0x40)
+            .collect::<Vec<_>>(),
+        [0x1028]
+    );
+    assert_eq!(
+        buffer.find_pattern(ida_pat!("62 6F"), 0x1000, 0x29).count(),
+        0
+    );
+}
+
+#[test]
+fn test_snapshot_source() {
+    let mut snapshot = Snapshot::from_regions(vec![SnapshotRegion {
+        address: 0x1000,
+        data: layout(),
+    }]);
+    assert_eq!(player_name(&snapshot, 0x1000).unwrap(), "alice");
+
+    snapshot.write_exact(0x1028, b"eve\0").unwrap();
+    assert_eq!(player_name(&snapshot, 0x1000).unwrap(), "eve");
+    assert!(snapshot.write_exact(0x103E, b"abc").is_err());
+}
+
+#[test]
+fn test_local_source() {
+    let data = Box::new(*b"\x00\x00\xDE\xAD\xBE\xEF\x00");
+    let base = data.as_ptr() as usize;
+    let memory = unsafe { LocalMemory::new() };
+
+    assert_eq!(
+        memory.find_pattern(ida_pat!("DE AD"), base, 7).next(),
+        Some(base + 2)
+    );
+    assert_eq!(memory.read::<[u8; 2]>(base + 4).unwrap(), [0xBE, 0xEF]);
+}
+
+#[cfg(all(target_os = "linux", feature = "external"))]
+#[test]
+fn test_process_source_chunks() {
+    let p = memflex::external::find_process_by_id(std::process::id()).unwrap();
+
+    unsafe {
+        let page = 0x1000;
+        let map = libc::mmap(
+            core::ptr::null_mut(),
+            page * 4,
+            libc::PROT_READ | libc::PROT_WRITE,
+            libc::MAP_PRIVATE | libc::MAP_ANONYMOUS,
+            -1,
+            0,
+        ) as usize;
+        assert_ne!(map, libc::MAP_FAILED as usize);
+
+        // Pattern crossing the first page boundary, one in the unreadable page and one after it.
+        let marker = [0x13, 0x37, 0xC0, 0xDE];
+        for address in [map + page - 2, map + page * 2 + 8, map + page * 3 + 16] {
+            core::ptr::copy_nonoverlapping(marker.as_ptr(), address as *mut u8, 4);
+        }
+        libc::mprotect((map + page * 2) as _, page, libc::PROT_NONE);
+
+        let found = MemorySource::find_pattern(&p, &marker[..], map, page * 4).collect::<Vec<_>>();
+        assert_eq!(found, [map + page - 2, map + page * 3 + 16]);
+
+        libc::munmap(map as _, page * 4);
+    }
+}
+
+/// Memory with an unreadable range, reads stop right before it.
+struct Holey {
+    buffer: MemoryBuffer<Vec<u8>>,
+    hole: core::ops::Range<usize>,
+}
+
+impl MemorySource for Holey {
+    fn read_buf(&self, address: usize, buf: &mut [u8]) -> memflex::Result<usize> {
+        if self.hole.contains(&address) {
+            return Err(MfError::InvalidAddress);
+        }
+
+        let len = if address < self.hole.start {
+            buf.len().min(self.hole.start - address)
+        } else {
+            buf.len()
+        };
+        self.buffer.read_buf(address, &mut buf[..len])
+    }
+}
+
+#[test]
+fn test_read_buf_partial() {
+    let memory = Holey {
+        buffer: MemoryBuffer::new(0x10000, vec![1; 0x4000]),
+        hole: 0x11000..0x13000,
+    };
+
+    let mut buf = vec![0; 0x4010];
+    let spans = memory.read_buf_partial(0x10000, &mut buf, 0xCC).unwrap();
+    assert_eq!(spans, [0x10000..0x11000, 0x13000..0x14000]);
+    assert!(buf[..0x1000].iter().all(|b| *b == 1));
+    assert!(buf[0x1000..0x3000].iter().all(|b| *b == 0xCC));
+    assert!(buf[0x3000..0x4000].iter().all(|b| *b == 1));
+    assert!(buf[0x4000..].iter().all(|b| *b == 0xCC));
+
+    let mut buf = [0; 0x10];
+    let spans = memory.read_buf_partial(0x11010, &mut buf, 0).unwrap();
+    assert!(spans.is_empty());
+}
+
+#[cfg(all(target_os = "linux", feature = "external"))]
+#[test]
+fn test_process_read_buf_partial() {
+    let p = memflex::external::find_process_by_id(std::process::id()).unwrap();
+
+    unsafe {
+        let page = 0x1000;
+        let map = libc::mmap(
+            core::ptr::null_mut(),
+            page * 5,
+            libc::PROT_READ | libc::PROT_WRITE,
+            libc::MAP_PRIVATE | libc::MAP_ANONYMOUS,
+            -1,
+            0,
+        ) as usize;
+        assert_ne!(map, libc::MAP_FAILED as usize);
+        core::ptr::write_bytes(map as *mut u8, 0x11, page * 5);
+        libc::mprotect((map + page) as _, page * 2, libc::PROT_NONE);
+
+        let mut buf = vec![0; page * 4];
+        let spans = p.read_buf_partial(map + 8, &mut buf, 0xFF).unwrap();
+        assert_eq!(
+            spans,
+            [map + 8..map + page, map + page * 3..map + page * 4 + 8]
+        );
+        assert!(buf[..page - 8].iter().all(|b| *b == 0x11));
+        assert!(buf[page - 8..page * 3 - 8].iter().all(|b| *b == 0xFF));
+        assert!(buf[page * 3 - 8..].iter().all(|b| *b == 0x11));
+
+        libc::munmap(map as _, page * 5);
+    }
+}
@@ -0,0 +1,56 @@
+#![cfg(all(target_os = "linux", target_arch = "x86_64", feature = "external"))]
+
+use memflex::external::{find_process_by_id, spawn_suspended, ProcessState};
+use std::time::Duration;
+
+#[test]
+fn test_spawn_suspended() {
+    let (p, mut handle) =
+        spawn_suspended("/bin/sleep", ["0.1"], std::iter::empty::<(&str, &str)>()).unwrap();
+
+    assert_eq!(p.state().unwrap(), ProcessState::TracingStop);
+    assert!(p.find_module("libc.so.6").is_err());
+
+    let entry = handle.entry_point().unwrap();
+    handle.run_until(entry).unwrap();
+    assert_eq!(handle.regs().unwrap().rip as usize, entry);
+    assert!(p.find_module("libc.so.6").is_ok());
+
+    handle.inject_library("libm.so.6").unwrap();
+    assert!(p.find_module("libm.so.6").is_ok());
+
+    let mut child = handle.resume().unwrap();
+    assert!(p.wait_exit(Some(Duration::from_secs(5))).unwrap());
+    assert!(child.wait().unwrap().success());
+}
+
+#[test]
+fn test_inject_library_breakpoint_and_signal() {
+    use std::os::unix::process::ExitStatusExt;
+
+    let (p, mut handle) =
+        spawn_suspended("/bin/sleep", ["0.1"], std::iter::empty::<(&str, &str)>()).unwrap();
+    handle.run_until(handle.entry_point().unwrap()).unwrap();
+
+    // Breakpoint at the start of `dlopen` in the process.
+    let ours = find_process_by_id(std::process::id())
+        .unwrap()
+        .find_module("libc.so.6")
+        .unwrap();
+    let theirs = p.find_module("libc.so.6").unwrap();
+    let dlopen = unsafe { libc::dlsym(libc::RTLD_DEFAULT, c"dlopen".as_ptr()) } as usize;
+    let dlopen = theirs.base as usize + (dlopen - ours.base as usize);
+    handle.set_breakpoint(dlopen).unwrap();
+
+    // Received during the call and delivered after it.
+    unsafe { libc::kill(p.id() as _, libc::SIGTERM) };
+    handle.inject_library("libm.so.6").unwrap();
+    assert!(p.find_module("libm.so.6").is_ok());
+
+    let mut byte = [0];
+    handle.read_buf(dlopen, &mut byte).unwrap();
+    assert_eq!(byte, [0xCC]);
+
+    let mut child = handle.resume().unwrap();
+    assert_eq!(child.wait().unwrap().signal(), Some(libc::SIGTERM));
+}
@@ -0,0 +1,30 @@
+#![cfg(all(target_os = "linux", feature = "internal"))]
+
+use memflex::internal::{find_module_by_name, find_symbol, modules, symbolize};
+
+#[test]
+fn test_find_symbol() {
+    let libc = find_module_by_name("libc.so.6").unwrap();
+    let open = find_symbol("libc.so.6", "open").unwrap();
+
+    assert!(open as usize >= libc.base as usize);
+    assert!((open as usize) < libc.base as usize + libc.size);
+    assert!(find_symbol("libc.so.6", "definitely_not_a_symbol").is_none());
+}
+
+#[test]
+fn test_symbolize() {
+    let getpid = find_symbol("libc.so.6", "getpid").unwrap() as usize;
+
+    let info = symbolize(getpid + 4).unwrap();
+    assert_eq!(info.module.name, "libc.so.6");
+    // Aliases such as `__getpid` share the address.
+    let name = info.name.unwrap();
+    assert_eq!(find_symbol("libc.so.6", &name).unwrap() as usize, getpid);
+    assert_eq!(info.address as usize, getpid);
+    assert_eq!(info.offset, 4);
+
+    let exe = modules().next().unwrap();
+    let info = symbolize(exe.base as usize).unwrap();
+    assert_eq!(info.module.base, exe.base);
+}
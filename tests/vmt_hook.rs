@@ -0,0 +1,149 @@
+#![cfg(all(target_os = "linux", feature = "internal"))]
+
+use memflex::{
+    internal::{allocate, protect, protection, ShadowVmt, VmtHook},
+    types::{Protection, VmtPtr},
+};
+use std::sync::OnceLock;
+
+type Method = extern "C" fn(*const Object) -> i32;
+
+#[repr(C)]
+struct Object {
+    vmt: VmtPtr,
+    value: i32,
+}
+
+extern "C" fn value(this: *const Object) -> i32 {
+    unsafe { (*this).value }
+}
+
+extern "C" fn double(this: *const Object) -> i32 {
+    unsafe { (*this).value * 2 }
+}
+
+extern "C" fn hooked(_: *const Object) -> i32 {
+    -1
+}
+
+extern "C" fn other(_: *const Object) -> i32 {
+    -2
+}
+
+/// Fake offset to top and type info entries that go before the methods.
+const RTTI: [usize; 2] = [0x1111, 0x2222];
+
+/// Read-only table with fake RTTI entries in front, shared by all objects.
+fn table() -> *const usize {
+    static TABLE: OnceLock<usize> = OnceLock::new();
+
+    *TABLE.get_or_init(|| {
+        let page = allocate(None, 0x1000, Protection::RW).unwrap() as usize;
+        let entries = [
+            RTTI[0],
+            RTTI[1],
+            value as Method as usize,
+            double as Method as usize,
+            0,
+        ];
+        unsafe { core::ptr::copy_nonoverlapping(entries.as_ptr(), page as *mut usize, 5) };
+        protect(page, 0x1000, Protection::R).unwrap();
+        page + 2 * size_of::<usize>()
+    }) as _
+}
+
+fn object(value: i32) -> Object {
+    Object {
+        vmt: VmtPtr::new(table()),
+        value,
+    }
+}
+
+fn call(object: &Object, index: usize) -> i32 {
+    unsafe { object.vmt.at::<Method>(index)(object) }
+}
+
+#[test]
+fn test_shadow_vmt() {
+    let mut first = object(10);
+    let second = object(20);
+    let original = first.vmt.as_ptr();
+
+    let mut shadow = unsafe { ShadowVmt::new_terminated(&mut first.vmt) };
+    assert_ne!(first.vmt.as_ptr(), original);
+    assert_eq!(shadow.original_table().as_ptr(), original);
+    // RTTI entries are copied too.
+    assert_eq!(
+        unsafe { [*first.vmt.as_ptr().sub(2), *first.vmt.as_ptr().sub(1)] },
+        RTTI
+    );
+
+    let value_fn: Method = unsafe { shadow.hook(0, hooked as Method) };
+    let double_fn: Method = unsafe { shadow.hook(1, other as Method) };
+    assert_eq!(value_fn(&first), 10);
+    assert_eq!(double_fn(&first), 20);
+    assert_eq!((call(&first, 0), call(&first, 1)), (-1, -2));
+    assert_eq!((call(&second, 0), call(&second, 1)), (20, 40));
+
+    shadow.unhook(1);
+    assert_eq!((call(&first, 0), call(&first, 1)), (-1, 20));
+
+    drop(shadow);
+    assert_eq!(first.vmt.as_ptr(), original);
+    assert_eq!((call(&first, 0), call(&first, 1)), (10, 20));
+}
+
+#[test]
+#[should_panic]
+fn test_shadow_vmt_out_of_bounds() {
+    let mut object = object(1);
+    let mut shadow = unsafe { ShadowVmt::new(&mut object.vmt, 2) };
+    unsafe { shadow.hook(2, hooked as Method) };
+}
+
+#[test]
+fn test_vmt_hook_in_place() {
+    let first = object(10);
+    let second = object(20);
+    let entry = first.vmt.as_ptr() as usize;
+    let prot = protection(entry).unwrap();
+    assert!(!prot.write());
+
+    let mut hook = unsafe { VmtHook::new(&first.vmt) };
+    let original: Method = unsafe { hook.hook(1, hooked as Method).unwrap() };
+    assert_eq!(original(&first), 20);
+    assert_eq!((call(&first, 1), call(&second, 1)), (-1, -1));
+    assert_eq!(protection(entry).unwrap(), prot);
+
+    // Hooking again keeps the very first original.
+    let again: Method = unsafe { hook.hook(1, other as Method).unwrap() };
+    assert_eq!(again as usize, original as usize);
+    assert_eq!(call(&second, 1), -2);
+
+    unsafe { hook.hook(0, hooked as Method).unwrap() };
+    assert_eq!(call(&second, 0), -1);
+    hook.unhook(0).unwrap();
+    assert_eq!(call(&second, 0), 20);
+    assert!(unsafe { hook.original::<Method>(0) }.is_none());
+
+    drop(hook);
+    assert_eq!((call(&first, 0), call(&first, 1)), (10, 20));
+    assert_eq!(protection(entry).unwrap(), prot);
+}
+
+#[test]
+fn test_vmt_hook_writable() {
+    let table = Box::new([value as Method, double as Method]);
+    let object = Object {
+        vmt: VmtPtr::new(table.as_ptr().cast()),
+        value: 3,
+    };
+
+    let mut hook = unsafe { VmtHook::new(&object.vmt) };
+    unsafe { hook.hook(0, other as Method).unwrap() };
+    assert_eq!(call(&object, 0), -2);
+    drop(hook);
+
+    assert_eq!(call(&object, 0), 3);
+    assert!(protection(table.as_ptr() as usize).unwrap().write());
+}
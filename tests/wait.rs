@@ -0,0 +1,31 @@
+#![cfg(all(target_os = "linux", feature = "external"))]
+
+use memflex::{
+    external::{find_process_by_id, wait_for_process, Wait},
+    MfError,
+};
+use std::time::{Duration, Instant};
+
+#[test]
+fn test_wait_timeout() {
+    let start = Instant::now();
+    let wait = Wait::from(Duration::from_millis(50)).interval(Duration::from_millis(10));
+
+    assert!(matches!(
+        wait_for_process("memflex-no-such-process", wait),
+        Err(MfError::ProcessNotFound)
+    ));
+    assert!(start.elapsed() >= Duration::from_millis(50));
+}
+
+#[test]
+fn test_wait_for_module() {
+    let p = find_process_by_id(std::process::id()).unwrap();
+    let libc = p.wait_for_module("libc.so.6", Wait::forever()).unwrap();
+    assert_eq!(libc.name, "libc.so.6");
+
+    assert!(matches!(
+        p.wait_for_module("libnothing.so", Duration::from_millis(20)),
+        Err(MfError::ModuleNotFound)
+    ));
+}
@@ -0,0 +1,91 @@
+#![cfg(all(target_os = "linux", feature = "external"))]
+
+use memflex::external::{find_process_by_id, Location, Watcher};
+use std::{
+    sync::{
+        atomic::{AtomicU32, AtomicU64, Ordering},
+        Arc, Mutex,
+    },
+    thread::sleep,
+    time::Duration,
+};
+
+#[test]
+fn test_watch_callback() {
+    let values = Box::new([AtomicU32::new(1), AtomicU32::new(2)]);
+    let p = find_process_by_id(std::process::id()).unwrap();
+    let watcher = Watcher::new(p, Duration::from_millis(1));
+
+    let seen = Arc::new(Mutex::new(vec![]));
+    let _first = watcher.watch(values[0].as_ptr() as usize, {
+        let seen = seen.clone();
+        move |old: u32, new: u32| seen.lock().unwrap().push((0, old, new))
+    });
+    let second = watcher.watch(values[1].as_ptr() as usize, {
+        let seen = seen.clone();
+        move |old: u32, new: u32| seen.lock().unwrap().push((1, old, new))
+    });
+
+    sleep(Duration::from_millis(50));
+    values[0].store(10, Ordering::SeqCst);
+    values[1].store(20, Ordering::SeqCst);
+    sleep(Duration::from_millis(50));
+
+    drop(second);
+    values[1].store(30, Ordering::SeqCst);
+    sleep(Duration::from_millis(50));
+
+    let mut seen = seen.lock().unwrap().clone();
+    seen.sort_unstable();
+    assert_eq!(seen, [(0, 1, 10), (1, 2, 20)]);
+}
+
+#[test]
+fn test_watch_channel() {
+    let value = Box::new(AtomicU64::new(5));
+    let pointer = Box::new(value.as_ptr() as usize);
+
+    let p = find_process_by_id(std::process::id()).unwrap();
+    let watcher = Watcher::new(p, Duration::from_millis(1));
+
+    let (_handle, changes) = watcher.watch_channel::<u64>(Location::Multilevel {
+        base: &*pointer as *const usize as usize,
+        offsets: vec![0, 0],
+    });
+
+    sleep(Duration::from_millis(50));
+    value.store(6, Ordering::SeqCst);
+    assert_eq!(
+        changes.recv_timeout(Duration::from_secs(5)).unwrap(),
+        (5, 6)
+    );
+    assert!(watcher.is_running());
+}
+
+#[test]
+fn test_watch_unwatch_in_callback() {
+    let value = Box::new(AtomicU32::new(1));
+    let p = find_process_by_id(std::process::id()).unwrap();
+    let watcher = Watcher::new(p, Duration::from_millis(1));
+
+    // The callback removes its own watch, which locks the watched values.
+    let handle = Arc::new(Mutex::new(None));
+    let calls = Arc::new(AtomicU32::new(0));
+    *handle.lock().unwrap() = Some(watcher.watch(value.as_ptr() as usize, {
+        let (handle, calls) = (handle.clone(), calls.clone());
+        move |_: u32, _: u32| {
+            calls.fetch_add(1, Ordering::SeqCst);
+            drop(handle.lock().unwrap().take());
+        }
+    }));
+
+    sleep(Duration::from_millis(50));
+    value.store(2, Ordering::SeqCst);
+    sleep(Duration::from_millis(50));
+    value.store(3, Ordering::SeqCst);
+    sleep(Duration::from_millis(50));
+
+    assert_eq!(calls.load(Ordering::SeqCst), 1);
+    assert!(handle.lock().unwrap().is_none());
+    assert!(watcher.is_running());
+}
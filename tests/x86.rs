@@ -0,0 +1,196 @@
+use memflex::x86::{decode, Field, OpcodeMap, Prefixes, MAX_LEN};
+
+/// Bytes of an instruction, its length, displacement and immediate.
+type Case = (
+    &'static [u8],
+    usize,
+    Option<(usize, usize)>,
+    Option<(usize, usize)>,
+);
+
+const CASES: &[Case] = &[
+    // push rbp
+    (b"\x55", 1, None, None),
+    // mov rbp, rsp
+    (b"\x48\x89\xE5", 3, None, None),
+    // sub rsp, 0x28
+    (b"\x48\x83\xEC\x28", 4, None, Some((3, 1))),
+    // sub rsp, 0x1000
+    (b"\x48\x81\xEC\x00\x10\x00\x00", 7, None, Some((3, 4))),
+    // mov eax, [rsp + 0x10]
+    (b"\x8B\x44\x24\x10", 4, Some((3, 1)), None),
+    // mov eax, [rax*4 + 0x1000]
+    (b"\x8B\x04\x85\x00\x10\x00\x00", 7, Some((3, 4)), None),
+    // mov dword ptr [rbp - 8], 0x11223344
+    (
+        b"\xC7\x45\xF8\x44\x33\x22\x11",
+        7,
+        Some((2, 1)),
+        Some((3, 4)),
+    ),
+    // mov word ptr [rax], 0x1234
+    (b"\x66\xC7\x00\x34\x12", 5, None, Some((3, 2))),
+    // movabs rax, 0x1122334455667788
+    (
+        b"\x48\xB8\x88\x77\x66\x55\x44\x33\x22\x11",
+        10,
+        None,
+        Some((2, 8)),
+    ),
+    // movabs al, [0x1122334455667788]
+    (
+        b"\xA0\x88\x77\x66\x55\x44\x33\x22\x11",
+        9,
+        None,
+        Some((1, 8)),
+    ),
+    // test byte ptr [rdi], 1
+    (b"\xF6\x07\x01", 3, None, Some((2, 1))),
+    // not dword ptr [rdi]
+    (b"\xF7\x17", 2, None, None),
+    // lock cmpxchg [rdi], ecx
+    (b"\xF0\x0F\xB1\x0F", 4, None, None),
+    // nop word ptr cs:[rax + rax + 0]
+    (
+        b"\x66\x2E\x0F\x1F\x84\x00\x00\x00\x00\x00",
+        10,
+        Some((6, 4)),
+        None,
+    ),
+    // pshufd xmm0, xmm1, 0x1B
+    (b"\x66\x0F\x70\xC1\x1B", 5, None, Some((4, 1))),
+    // pshufb xmm0, [rip + 0x100]
+    (
+        b"\x66\x0F\x38\x00\x05\x00\x01\x00\x00",
+        9,
+        Some((5, 4)),
+        None,
+    ),
+    // roundss xmm0, xmm1, 4
+    (b"\x66\x0F\x3A\x0A\xC1\x04", 6, None, Some((5, 1))),
+    // vzeroupper
+    (b"\xC5\xF8\x77", 3, None, None),
+    // vmovups ymm0, [rax + 0x20]
+    (b"\xC5\xFC\x10\x40\x20", 5, Some((4, 1)), None),
+    // vpermq ymm0, ymm1, 0x4E
+    (b"\xC4\xE3\xFD\x00\xC1\x4E", 6, None, Some((5, 1))),
+    // vmovdqu64 zmm0, [rdi]
+    (b"\x62\xF1\xFE\x48\x6F\x07", 6, None, None),
+    // enter 0x20, 0
+    (b"\xC8\x20\x00\x00", 4, None, Some((1, 3))),
+    // ret 8
+    (b"\xC2\x08\x00", 3, None, Some((1, 2))),
+    // syscall
+    (b"\x0F\x05", 2, None, None),
+    // endbr64
+    (b"\xF3\x0F\x1E\xFA", 4, None, None),
+];
+
+#[test]
+fn test_decode_lengths() {
+    for &(code, len, disp, imm) in CASES {
+        let ins = decode(code).unwrap_or_else(|| panic!("{code:02X?} wasn't decoded"));
+        let field = |f: Option<Field>| f.map(|f| (f.offset, f.size));
+
+        assert_eq!(
+            (ins.len, field(ins.disp), field(ins.imm)),
+            (len, disp, imm),
+            "{code:02X?}"
+        );
+        assert!(!ins.relative_branch, "{code:02X?}");
+
+        // Truncated instructions aren't decoded.
+        assert_eq!(decode(&code[..len - 1]), None, "{code:02X?}");
+    }
+}
+
+#[test]
+fn test_decode_fields() {
+    // lock add qword ptr fs:[rax + rcx*8 + 0x10], 1
+    let ins = decode(b"\xF0\x64\x48\x83\x44\xC8\x10\x01").unwrap();
+    assert_eq!(ins.prefixes, Prefixes::LOCK | Prefixes::FS | Prefixes::REX);
+    assert_eq!(ins.rex, Some(0x48));
+    assert_eq!((ins.map, ins.opcode), (OpcodeMap::Primary, 0x83));
+    assert_eq!((ins.modrm, ins.sib), (Some(0x44), Some(0xC8)));
+    assert_eq!(ins.disp, Some(Field { offset: 6, size: 1 }));
+
+    // vpermq
+    let ins = decode(b"\xC4\xE3\xFD\x00\xC1\x4E").unwrap();
+    assert_eq!(ins.prefixes, Prefixes::VEX);
+    assert_eq!(
+        (ins.map, ins.opcode, ins.rex),
+        (OpcodeMap::Escape0F3A, 0x00, None)
+    );
+
+    let ins = decode(b"\x62\xF1\xFE\x48\x6F\x07").unwrap();
+    assert_eq!(
+        (ins.prefixes, ins.map),
+        (Prefixes::EVEX, OpcodeMap::Escape0F)
+    );
+}
+
+#[test]
+fn test_decode_relative() {
+    let address = 0x10000;
+
+    // (code, length, target)
+    let cases: &[(&[u8], usize, usize)] = &[
+        // jmp -2
+        (b"\xEB\xFE", 2, address),
+        // jz +0x10
+        (b"\x74\x10", 2, address + 0x12),
+        // call +0x100
+        (b"\xE8\x00\x01\x00\x00", 5, address + 0x105),
+        // jmp -0x100
+        (b"\xE9\x00\xFF\xFF\xFF", 5, address + 5 - 0x100),
+        // jne +0x20
+        (b"\x0F\x85\x20\x00\x00\x00", 6, address + 0x26),
+        // loop -2
+        (b"\xE2\xFE", 2, address),
+        // xbegin +0x10
+        (b"\xC7\xF8\x10\x00\x00\x00", 6, address + 0x16),
+    ];
+    for &(code, len, target) in cases {
+        let ins = decode(code).unwrap();
+        assert!(ins.relative_branch && !ins.rip_relative, "{code:02X?}");
+        assert_eq!(ins.len, len);
+        assert_eq!(ins.target(address, code), Some(target), "{code:02X?}");
+    }
+
+    // lea rax, [rip - 0x20]
+    let code = b"\x48\x8D\x05\xE0\xFF\xFF\xFF";
+    let ins = decode(code).unwrap();
+    assert!(ins.rip_relative && !ins.relative_branch);
+    assert_eq!(ins.target(address, code), Some(address + 7 - 0x20));
+
+    // cmp byte ptr [rip + 0x10], 0x7F, the immediate follows the displacement.
+    let code = b"\x80\x3D\x10\x00\x00\x00\x7F";
+    let ins = decode(code).unwrap();
+    assert_eq!(ins.len, 7);
+    assert_eq!(ins.target(address, code), Some(address + 0x17));
+
+    // jmp qword ptr [rax] isn't relative.
+    let ins = decode(b"\xFF\x20").unwrap();
+    assert_eq!(ins.target(address, b"\xFF\x20"), None);
+}
+
+#[test]
+fn test_decode_invalid() {
+    // Invalid in 64-bit mode: push es, aaa, pusha, far jmp, 3DNow!.
+    for code in [
+        &b"\x06"[..],
+        b"\x37",
+        b"\x60",
+        b"\xEA\x00\x00\x00\x00\x00\x00",
+        b"\x0F\x0F\xC1\x0D",
+    ] {
+        assert_eq!(decode(code), None, "{code:02X?}");
+    }
+
+    // Too many prefixes.
+    let mut code = [0x66; MAX_LEN + 1];
+    code[MAX_LEN] = 0x90;
+    assert_eq!(decode(&code), None);
+    assert_eq!(decode(&code[MAX_LEN - 1..]).map(|i| i.len), Some(2));
+    assert_eq!(decode(&[]), None);
+}